@@ -1,9 +1,11 @@
 use ahash::{AHashMap, AHashSet};
 use serde::de::{DeserializeSeed, IgnoredAny, Visitor};
-use serde::Deserialize;
+use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct};
+use serde::{Deserialize, Serialize, Serializer};
 
 use super::rules::ModernPropertyRules;
 use crate::blocks::raw::property::{EnumProperty, PropertyKind};
+use crate::util::identifier::Identifier;
 
 /// A collection of possible property collisions in raw data.
 ///
@@ -28,6 +30,12 @@ pub struct CollisionList<'raw> {
 impl<'raw> CollisionList<'raw> {
     pub fn should_exit(&self) -> bool { !self.by_name.is_empty() }
 
+    /// Property names that are used for more than one set of values.
+    pub fn by_name(&self) -> &AHashMap<&'raw str, AHashSet<EnumProperty<'raw>>> { &self.by_name }
+
+    /// Sets of values that are used by more than one property name.
+    pub fn by_values(&self) -> &AHashMap<EnumProperty<'raw>, AHashSet<&'raw str>> { &self.by_values }
+
     /// Displays a summary of the different collisions found in the raw data.
     pub fn display(&self) {
         if !self.by_name.is_empty() {
@@ -55,6 +63,67 @@ impl<'raw> CollisionList<'raw> {
     }
 }
 
+/// Serializes the collisions in a stable order, value collisions are listed
+/// as objects because their values can't be used as keys.
+impl<'raw> Serialize for CollisionList<'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        struct ByName<'b, 'raw>(&'b AHashMap<&'raw str, AHashSet<EnumProperty<'raw>>>);
+        struct ByValues<'b, 'raw>(&'b AHashMap<EnumProperty<'raw>, AHashSet<&'raw str>>);
+
+        impl<'b, 'raw> Serialize for ByName<'b, 'raw> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut names: Vec<_> = self.0.iter().collect();
+                names.sort_by_key(|(name, _)| **name);
+                let mut map = serializer.serialize_map(Some(names.len()))?;
+                for (name, values) in names {
+                    let mut values: Vec<_> = values.iter().map(EnumProperty::fields).collect();
+                    values.sort();
+                    map.serialize_entry(name, &values)?;
+                }
+                map.end()
+            }
+        }
+
+        impl<'b, 'raw> Serialize for ByValues<'b, 'raw> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                #[derive(Serialize)]
+                struct Collision<'b, 'raw> {
+                    values: &'b [&'raw str],
+                    names: Vec<&'raw str>,
+                }
+
+                let mut collisions: Vec<_> = self
+                    .0
+                    .iter()
+                    .map(|(values, names)| {
+                        let mut names: Vec<_> = names.iter().copied().collect();
+                        names.sort_unstable();
+                        Collision {
+                            values: values.fields(),
+                            names,
+                        }
+                    })
+                    .collect();
+                collisions.sort_by(|a, b| a.values.cmp(b.values));
+                let mut seq = serializer.serialize_seq(Some(collisions.len()))?;
+                for collision in collisions {
+                    seq.serialize_element(&collision)?;
+                }
+                seq.end()
+            }
+        }
+
+        let mut report = serializer.serialize_struct("CollisionList", 3)?;
+        report.serialize_field("fatal", &self.should_exit())?;
+        report.serialize_field("by_name", &ByName(&self.by_name))?;
+        report.serialize_field("by_values", &ByValues(&self.by_values))?;
+        report.end()
+    }
+}
+
 pub struct CollisionRuleProvider<'a, 'raw>(Option<&'a ModernPropertyRules<'raw>>);
 
 impl<'a, 'raw> CollisionRuleProvider<'a, 'raw> {
@@ -65,8 +134,8 @@ impl<'a, 'raw> CollisionRuleProvider<'a, 'raw> {
     /// - First it makes sure the property name is not `"type"`, this will get
     ///   transformed into `"kind"`
     /// - Secondly it uses the rules, if present, to replace the property name
-    ///   if there's a match based on the property values
-    pub fn transform<'b, I>(&'b self, properties: I) -> impl Iterator<Item = (&'raw str, PropertyKind<'raw>)> + 'b
+    ///   if there's a rule matching the property of this block
+    pub fn transform<'b, I>(&'b self, block: &'b Identifier<'raw>, properties: I) -> impl Iterator<Item = (&'raw str, PropertyKind<'raw>)> + 'b
     where
         I: IntoIterator<Item = (&'raw str, PropertyKind<'raw>)> + 'b,
     {
@@ -77,7 +146,7 @@ impl<'a, 'raw> CollisionRuleProvider<'a, 'raw> {
                 name
             };
             if let Some(rules) = self.0 {
-                rules.transform(name, property)
+                rules.transform(block, name, property)
             } else {
                 (name, property)
             }
@@ -99,8 +168,8 @@ impl<'a, 'raw, 'de: 'raw> Visitor<'de> for CollisionRuleProvider<'a, 'raw> {
         let mut by_name = AHashMap::<&'raw str, AHashSet<EnumProperty>>::new();
         let mut by_values = AHashMap::<EnumProperty<'raw>, AHashSet<&'raw str>>::new();
 
-        while let Some((_, data)) = map.next_entry::<IgnoredAny, RawBlockData<'de>>()? {
-            for (name, property) in self.transform(data.properties) {
+        while let Some((identifier, data)) = map.next_entry::<Identifier<'de>, RawBlockData<'de>>()? {
+            for (name, property) in self.transform(&identifier, data.properties) {
                 if let PropertyKind::Enum(property) = property {
                     if let Some(names) = by_values.get_mut(&property) {
                         names.insert(name);
@@ -135,9 +204,34 @@ impl<'a, 'raw, 'de: 'raw> DeserializeSeed<'de> for CollisionRuleProvider<'a, 'ra
 }
 
 #[derive(Debug, Deserialize)]
-struct RawBlockData<'raw> {
+pub(super) struct RawBlockData<'raw> {
     #[serde(borrow, default)]
-    properties: AHashMap<&'raw str, PropertyKind<'raw>>,
+    pub(super) properties: AHashMap<&'raw str, PropertyKind<'raw>>,
     #[serde(rename = "states")]
     _states: IgnoredAny,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    const BLOCKS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/blocks.json"));
+
+    #[test]
+    fn test_report() {
+        let collisions = crate::check_collisions(BLOCKS, None).unwrap();
+        let report = serde_json::to_value(&collisions).unwrap();
+        assert_eq!(
+            report,
+            json!({
+                "fatal": true,
+                "by_name": {
+                    "facing": [["down", "north", "south", "west", "east"], ["north", "south", "west", "east"]],
+                },
+                "by_values": [
+                    {"values": ["up", "side", "none"], "names": ["east", "north"]},
+                ],
+            })
+        );
+    }
+}
@@ -1,7 +1,10 @@
+use std::borrow::Cow;
+
 use ahash::RandomState;
 use hashlink::LinkedHashMap;
 use serde::{Deserialize, Serialize};
 
+use super::lookup::{BlockStateIndex, LookupError};
 use super::MetaData;
 use crate::blocks::raw::property::EnumProperty;
 use crate::util::identifier::Identifier;
@@ -12,6 +15,20 @@ use crate::util::identifier::Identifier;
 pub type PropertyList<'raw> = LinkedHashMap<&'raw str, EnumProperty<'raw>, RandomState>;
 /// A shorter form of the block list of the compact format.
 pub type BlockList<'raw> = LinkedHashMap<Identifier<'raw>, ModernBlockData<'raw>, RandomState>;
+/// The resolved values of every property of a block, in network order.
+pub type PropertyValues<'raw> = Vec<(&'raw str, Vec<Cow<'raw, str>>)>;
+
+/// Splits a blockstate offset from the base id into the value index of every
+/// property, the first property being the most significant one.
+pub fn value_indices(values: &PropertyValues, offset: usize) -> Vec<usize> {
+    let mut indices = vec![0; values.len()];
+    let mut remaining = offset;
+    for ((_, values), index) in values.iter().zip(indices.iter_mut()).rev() {
+        *index = remaining % values.len();
+        remaining /= values.len();
+    }
+    indices
+}
 
 /// The compact blockstates format.
 ///
@@ -34,13 +51,16 @@ pub struct ModernBlockList<'raw> {
 }
 
 impl<'raw> ModernBlockList<'raw> {
-    pub(crate) fn new(metadata: Option<MetaData<'raw>>, properties: PropertyList<'raw>, blocks: BlockList<'raw>) -> Self {
+    pub fn new(metadata: Option<MetaData<'raw>>, properties: PropertyList<'raw>, blocks: BlockList<'raw>) -> Self {
         ModernBlockList {
             metadata,
             properties,
             blocks,
         }
     }
+
+    /// Builds an index to look up blockstates in this list.
+    pub fn index(&self) -> Result<BlockStateIndex<'_, 'raw>, LookupError> { BlockStateIndex::new(self) }
 }
 
 /// Compact way of identifying block data.
@@ -67,6 +87,37 @@ impl<'raw> ModernBlockData<'raw> {
             default_id,
         }
     }
+
+    /// Returns the id of the default blockstate of this block.
+    pub fn default_id(&self) -> i32 { self.default_id.unwrap_or(self.base_id) }
+
+    /// Resolves the possible values of every property of this block.
+    ///
+    /// The properties are returned in the same order as they appear in the
+    /// block, which is also the order used to calculate blockstate ids. If an
+    /// enum property can't be found in `properties`, its name is returned as
+    /// an error.
+    pub fn property_values(&self, properties: &PropertyList<'raw>) -> Result<PropertyValues<'raw>, &'raw str> {
+        self.kinds
+            .iter()
+            .map(|(&name, kind)| match kind.values(properties) {
+                Some(values) => Ok((name, values)),
+                None => Err(kind.name().unwrap_or(name)),
+            })
+            .collect()
+    }
+
+    /// Calculates the amount of blockstates of this block.
+    ///
+    /// Returns `None` if one of the enum properties can't be found in
+    /// `properties`.
+    pub fn state_count(&self, properties: &PropertyList<'raw>) -> Option<usize> {
+        let mut count = 1;
+        for (_, kind) in &self.kinds {
+            count *= kind.len(properties)?;
+        }
+        Some(count)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -83,4 +134,35 @@ impl<'raw> PropertyValue<'raw> {
     pub fn enum_name(value: &'raw str) -> Self { Self::Text(value) }
 
     pub fn range(start: u8, end: u8) -> Self { Self::Range([start, end]) }
+
+    /// Returns the name of the enum property this value refers to, if any.
+    pub fn name(&self) -> Option<&'raw str> {
+        match self {
+            PropertyValue::Text(text) if *text != "bool" => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the amount of values this property can take.
+    pub fn len(&self, properties: &PropertyList<'raw>) -> Option<usize> {
+        match self {
+            PropertyValue::Range([start, end]) => Some((end - start + 1) as usize),
+            PropertyValue::Text("bool") => Some(2),
+            PropertyValue::Text(name) => properties.get(name).map(|property| property.fields().len()),
+        }
+    }
+
+    /// Returns the values of this property in network order.
+    ///
+    /// Enum properties are looked up in `properties`, if no such property
+    /// exists `None` is returned.
+    pub fn values(&self, properties: &PropertyList<'raw>) -> Option<Vec<Cow<'raw, str>>> {
+        match self {
+            PropertyValue::Range([start, end]) => Some((*start..=*end).map(|value| Cow::Owned(value.to_string())).collect()),
+            PropertyValue::Text("bool") => Some(vec![Cow::Borrowed("true"), Cow::Borrowed("false")]),
+            PropertyValue::Text(name) => properties
+                .get(name)
+                .map(|property| property.fields().iter().map(|&value| Cow::Borrowed(value)).collect()),
+        }
+    }
 }
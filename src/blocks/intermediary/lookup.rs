@@ -0,0 +1,273 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use ahash::AHashMap;
+use thiserror::Error;
+
+use super::data::{value_indices, ModernBlockData, ModernBlockList, PropertyValues};
+use crate::util::blockstate::BlockStateString;
+use crate::util::identifier::Identifier;
+
+/// A single blockstate resolved from the compact format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockState<'raw> {
+    pub identifier: Identifier<'raw>,
+    pub id: i32,
+    /// The value of every property of the block, in network order
+    pub properties: Vec<(&'raw str, Cow<'raw, str>)>,
+}
+
+impl<'raw> BlockState<'raw> {
+    /// Returns the value of a property of this blockstate.
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(property, _)| *property == name)
+            .map(|(_, value)| value.as_ref())
+    }
+}
+
+impl<'raw> Display for BlockState<'raw> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let properties = self.properties.iter().map(|(name, value)| (*name, value.as_ref())).collect();
+        BlockStateString::new(self.identifier, properties).fmt(f)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LookupError {
+    #[error("Unknown block \"{0}\"")]
+    UnknownBlock(String),
+    #[error("Unknown property \"{property}\" for block \"{block}\"")]
+    UnknownProperty {
+        block: String,
+        property: String,
+    },
+    #[error("Property \"{property}\" of block \"{block}\" is specified more than once")]
+    DuplicateProperty {
+        block: String,
+        property: String,
+    },
+    #[error("Missing property \"{property}\" for block \"{block}\"")]
+    MissingProperty {
+        block: String,
+        property: String,
+    },
+    #[error("Invalid value \"{value}\" for property \"{property}\" of block \"{block}\"")]
+    InvalidValue {
+        block: String,
+        property: String,
+        value: String,
+    },
+}
+
+/// A block in the index together with its resolved property values.
+struct IndexedBlock<'a, 'raw> {
+    identifier: Identifier<'raw>,
+    data: &'a ModernBlockData<'raw>,
+    values: PropertyValues<'raw>,
+    state_count: usize,
+}
+
+impl<'a, 'raw> IndexedBlock<'a, 'raw> {
+    /// Resolves the blockstate at the given offset from the base id.
+    fn state(&self, offset: usize) -> BlockState<'raw> {
+        let properties = self
+            .values
+            .iter()
+            .zip(value_indices(&self.values, offset))
+            .map(|((name, values), index)| (*name, values[index].clone()))
+            .collect();
+        BlockState {
+            identifier: self.identifier,
+            id: self.data.base_id + offset as i32,
+            properties,
+        }
+    }
+}
+
+/// An index over a [`ModernBlockList`] to look up blockstates.
+///
+/// Building the index resolves the properties of every block once and sorts
+/// the blocks by their base id, this way looking up a blockstate id is a
+/// binary search and looking up a block is a hash lookup.
+pub struct BlockStateIndex<'a, 'raw> {
+    blocks: Vec<IndexedBlock<'a, 'raw>>,
+    identifiers: AHashMap<Identifier<'raw>, usize>,
+}
+
+impl<'a, 'raw> BlockStateIndex<'a, 'raw> {
+    pub fn new(data: &'a ModernBlockList<'raw>) -> Result<Self, LookupError> {
+        let mut blocks = data
+            .blocks
+            .iter()
+            .map(|(identifier, block)| {
+                let values = block
+                    .property_values(&data.properties)
+                    .map_err(|property| LookupError::UnknownProperty {
+                        block: identifier.to_string(),
+                        property: property.to_string(),
+                    })?;
+                Ok(IndexedBlock {
+                    identifier: *identifier,
+                    data: block,
+                    state_count: values.iter().map(|(_, values)| values.len()).product(),
+                    values,
+                })
+            })
+            .collect::<Result<Vec<_>, LookupError>>()?;
+        blocks.sort_by_key(|block| block.data.base_id);
+        let identifiers = blocks.iter().enumerate().map(|(index, block)| (block.identifier, index)).collect();
+
+        Ok(Self {
+            blocks,
+            identifiers,
+        })
+    }
+
+    /// Returns the blockstate with the given id.
+    pub fn state(&self, id: i32) -> Option<BlockState<'raw>> {
+        let index = match self.blocks.binary_search_by_key(&id, |block| block.data.base_id) {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        let block = &self.blocks[index];
+        let offset = (id - block.data.base_id) as usize;
+        if offset < block.state_count {
+            Some(block.state(offset))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the default blockstate of a block.
+    pub fn default_state(&self, identifier: &Identifier) -> Option<BlockState<'raw>> {
+        let block = self.block(identifier)?;
+        Some(block.state((block.data.default_id() - block.data.base_id) as usize))
+    }
+
+    /// Calculates the id of the blockstate with the given property values.
+    ///
+    /// Every property of the block needs to be specified exactly once.
+    pub fn state_id<'b, I>(&self, identifier: &Identifier, properties: I) -> Result<i32, LookupError>
+    where
+        I: IntoIterator<Item = (&'b str, &'b str)>,
+    {
+        self.find_id(identifier, properties, false)
+    }
+
+    /// Calculates the id of the blockstate described by a blockstate string.
+    ///
+    /// Properties that are not specified take their value from the default
+    /// blockstate of the block, a property can't be specified more than once.
+    pub fn resolve(&self, state: &BlockStateString) -> Result<i32, LookupError> { self.find_id(state.identifier(), state.properties().iter().copied(), true) }
+
+    fn find_id<'b, I>(&self, identifier: &Identifier, properties: I, defaults: bool) -> Result<i32, LookupError>
+    where
+        I: IntoIterator<Item = (&'b str, &'b str)>,
+    {
+        let block = self
+            .block(identifier)
+            .ok_or_else(|| LookupError::UnknownBlock(identifier.to_string()))?;
+        let mut indices = if defaults {
+            value_indices(&block.values, (block.data.default_id() - block.data.base_id) as usize)
+                .into_iter()
+                .map(Some)
+                .collect()
+        } else {
+            vec![None; block.values.len()]
+        };
+        let mut specified = vec![false; block.values.len()];
+        for (name, value) in properties {
+            let position = block
+                .values
+                .iter()
+                .position(|(property, _)| *property == name)
+                .ok_or_else(|| LookupError::UnknownProperty {
+                    block: identifier.to_string(),
+                    property: name.to_string(),
+                })?;
+            if std::mem::replace(&mut specified[position], true) {
+                return Err(LookupError::DuplicateProperty {
+                    block: identifier.to_string(),
+                    property: name.to_string(),
+                });
+            }
+            let index = block.values[position]
+                .1
+                .iter()
+                .position(|x| x == value)
+                .ok_or_else(|| LookupError::InvalidValue {
+                    block: identifier.to_string(),
+                    property: name.to_string(),
+                    value: value.to_string(),
+                })?;
+            indices[position] = Some(index);
+        }
+
+        let mut offset = 0;
+        for ((name, values), index) in block.values.iter().zip(indices) {
+            let index = index.ok_or_else(|| LookupError::MissingProperty {
+                block: identifier.to_string(),
+                property: name.to_string(),
+            })?;
+            offset = offset * values.len() + index;
+        }
+        Ok(block.data.base_id + offset as i32)
+    }
+
+    fn block(&self, identifier: &Identifier) -> Option<&IndexedBlock<'a, 'raw>> { self.identifiers.get(identifier).map(|&index| &self.blocks[index]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &str = r#"{
+        "properties": { "facing": ["north", "south"] },
+        "blocks": {
+            "minecraft:air": { "base": 0 },
+            "minecraft:stairs": { "properties": { "facing": "facing", "waterlogged": "bool" }, "base": 4, "default": 5 },
+            "minecraft:wheat": { "properties": { "age": [1, 3] }, "base": 1 }
+        }
+    }"#;
+
+    #[test]
+    fn test_state_lookup() {
+        let data: ModernBlockList = serde_json::from_str(DATA).unwrap();
+        let index = data.index().unwrap();
+
+        let state = index.state(6).unwrap();
+        assert_eq!(state.identifier, Identifier::from_location("stairs"));
+        assert_eq!(state.properties, vec![("facing", Cow::Borrowed("south")), ("waterlogged", Cow::Borrowed("true"))]);
+        assert_eq!(index.state(3).unwrap().property("age"), Some("3"));
+        assert_eq!(index.state(4).unwrap().to_string(), "minecraft:stairs[facing=north,waterlogged=true]");
+        assert_eq!(index.state(0).unwrap().properties, vec![]);
+        assert_eq!(index.state(8), None);
+        assert_eq!(index.state(-1), None);
+    }
+
+    #[test]
+    fn test_state_id_lookup() {
+        let data: ModernBlockList = serde_json::from_str(DATA).unwrap();
+        let index = data.index().unwrap();
+        let stairs = Identifier::from_location("stairs");
+
+        assert_eq!(index.state_id(&stairs, [("waterlogged", "false"), ("facing", "south")]), Ok(7));
+        assert_eq!(index.default_state(&stairs).unwrap().id, 5);
+        assert_eq!(index.default_state(&Identifier::from_location("wheat")).unwrap().id, 1);
+        assert!(matches!(index.state_id(&stairs, [("facing", "south")]), Err(LookupError::MissingProperty { .. })));
+        assert_eq!(
+            index.state_id(&stairs, [("facing", "north"), ("waterlogged", "true"), ("facing", "south")]),
+            Err(LookupError::DuplicateProperty {
+                block: "minecraft:stairs".to_string(),
+                property: "facing".to_string()
+            })
+        );
+        assert_eq!(index.resolve(&"stairs[facing=south]".try_into().unwrap()), Ok(7));
+        assert_eq!(index.resolve(&"wheat".try_into().unwrap()), Ok(1));
+        assert!(matches!(index.state_id(&stairs, [("facing", "east")]), Err(LookupError::InvalidValue { .. })));
+        assert!(matches!(index.state_id(&Identifier::from_location("stone"), []), Err(LookupError::UnknownBlock(_))));
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MetaData<'raw> {
     pub id: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
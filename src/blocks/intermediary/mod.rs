@@ -1,6 +1,9 @@
 pub mod collisions;
 pub mod data;
+pub mod lookup;
 pub mod metadata;
 pub mod rules;
+pub mod suggest;
+pub mod validate;
 
 pub use metadata::MetaData;
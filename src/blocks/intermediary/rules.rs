@@ -1,29 +1,399 @@
 use ahash::RandomState;
 use hashlink::LinkedHashMap;
-use serde::Deserialize;
+use serde::de::{Error, Visitor};
+use serde::ser::{SerializeMap, SerializeStruct};
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::blocks::raw::property::{EnumProperty, PropertyKind};
+use crate::util::identifier::Identifier;
 
-#[derive(Debug, Deserialize)]
-#[serde(from = "LinkedHashMap<&'raw str, EnumProperty<'raw>, RandomState>")]
+/// A set of rules renaming enum properties.
+///
+/// A rules file maps new property names to the rule selecting which
+/// properties get that name. In its simplest form, a rule is the list of
+/// values of the property:
+/// ```json
+/// { "hopper_facing": ["down", "north", "south", "west", "east"] }
+/// ```
+///
+/// A rule can also be scoped, using an object with the optional fields
+/// `values`, `property`, `blocks` and `namespace`:
+/// ```json
+/// {
+///     "stairs_facing": { "values": ["north", "south", "west", "east"], "blocks": ["*_stairs"] },
+///     "modded_color": { "property": "color", "namespace": "mymod" }
+/// }
+/// ```
+/// - `values` matches the values of the property, in order
+/// - `property` matches the name of the property, `type` is matched as `kind`
+/// - `blocks` matches block identifiers, a `*` matching any characters. Entries
+///   without a namespace only match the location of the block
+/// - `namespace` matches the namespace of the block
+///
+/// A scoped rule needs either `values` or `property`. When multiple rules
+/// match the same property, the most specific rule wins. Rules are compared
+/// on, in order:
+/// 1. how they select blocks: an exact block identifier, a pattern, a
+///    namespace, or no selection at all
+/// 2. whether they match the property name
+/// 3. whether they match the property values
+///
+/// If that still doesn't decide, the first rule in the file wins.
+#[derive(Debug, Clone)]
 pub struct ModernPropertyRules<'raw> {
-    #[serde(borrow, flatten)]
-    rule_data: LinkedHashMap<EnumProperty<'raw>, &'raw str, RandomState>,
+    rules: Vec<PropertyRule<'raw>>,
+    rule_data: LinkedHashMap<EnumProperty<'raw>, usize, RandomState>,
 }
 
 impl<'raw> ModernPropertyRules<'raw> {
-    pub fn transform(&self, name: &'raw str, property: PropertyKind<'raw>) -> (&'raw str, PropertyKind<'raw>) {
+    /// Constructs new rules, keeping all of them in the given order.
+    pub fn new(rules: Vec<PropertyRule<'raw>>) -> Self {
+        let mut rule_data = LinkedHashMap::with_hasher(RandomState::default());
+        for (index, rule) in rules.iter().enumerate() {
+            if let Some(values) = rule.flat_values() {
+                rule_data.entry(values.clone()).or_insert(index);
+            }
+        }
+        Self { rules, rule_data }
+    }
+
+    pub fn transform(&self, block: &Identifier, name: &'raw str, property: PropertyKind<'raw>) -> (&'raw str, PropertyKind<'raw>) {
         match &property {
-            PropertyKind::Enum(enum_property) => (self.rule_data.get(enum_property).cloned().unwrap_or(name), property),
+            PropertyKind::Enum(enum_property) => (self.find(block, name, enum_property).map_or(name, |rule| rule.name), property),
             _ => (name, property),
         }
     }
+
+    /// Finds the rule that applies to a property of a block.
+    pub fn find(&self, block: &Identifier, name: &str, values: &EnumProperty<'raw>) -> Option<&PropertyRule<'raw>> {
+        let flat = self.rule_data.get(values).map(|&index| &self.rules[index]);
+        let mut best: Option<(&PropertyRule<'raw>, Specificity)> = None;
+        for rule in self.rules.iter().filter(|rule| !rule.is_flat()) {
+            if let Some(specificity) = rule.matches(block, name, values) {
+                if best.is_none_or(|(_, best)| specificity > best) {
+                    best = Some((rule, specificity));
+                }
+            }
+        }
+        match (best, flat) {
+            (Some((rule, specificity)), Some(_)) if specificity > PropertyRule::FLAT => Some(rule),
+            (_, Some(flat)) => Some(flat),
+            (best, None) => best.map(|(rule, _)| rule),
+        }
+    }
+
+    /// Iterates over the rules in the order they were specified.
+    pub fn iter(&self) -> impl Iterator<Item = &PropertyRule<'raw>> { self.rules.iter() }
 }
 
 impl<'raw> From<LinkedHashMap<&'raw str, EnumProperty<'raw>, RandomState>> for ModernPropertyRules<'raw> {
     fn from(other: LinkedHashMap<&'raw str, EnumProperty<'raw>, RandomState>) -> Self {
+        Self::new(other.into_iter().map(|(name, values)| PropertyRule::flat(name, values)).collect())
+    }
+}
+
+impl<'raw, 'de: 'raw> Deserialize<'de> for ModernPropertyRules<'raw> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct RulesVisitor;
+
+        impl<'de> Visitor<'de> for RulesVisitor {
+            type Value = ModernPropertyRules<'de>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result { formatter.write_str("a map of property names to rules") }
+
+            /// Duplicate names are kept so they can be reported
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut rules = Vec::new();
+                while let Some(name) = map.next_key::<&'de str>()? {
+                    let rule = match map.next_value::<RawRule<'de>>()? {
+                        RawRule::Flat(values) => PropertyRule::flat(name, values),
+                        RawRule::Scoped {
+                            values,
+                            property,
+                            blocks,
+                            namespace,
+                        } => {
+                            if values.is_none() && property.is_none() {
+                                return Err(A::Error::custom(format!("rule \"{}\" needs either values or a property name", name)));
+                            }
+                            PropertyRule {
+                                name,
+                                values,
+                                property,
+                                blocks,
+                                namespace,
+                            }
+                        },
+                    };
+                    rules.push(rule);
+                }
+                Ok(ModernPropertyRules::new(rules))
+            }
+        }
+
+        deserializer.deserialize_map(RulesVisitor)
+    }
+}
+
+impl<'raw> Serialize for ModernPropertyRules<'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.rules.len()))?;
+        for rule in &self.rules {
+            map.serialize_entry(rule.name, rule)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum RawRule<'raw> {
+    #[serde(borrow)]
+    Flat(EnumProperty<'raw>),
+    Scoped {
+        #[serde(borrow, default)]
+        values: Option<EnumProperty<'raw>>,
+        #[serde(default)]
+        property: Option<&'raw str>,
+        #[serde(borrow, default)]
+        blocks: Vec<BlockPattern<'raw>>,
+        #[serde(default)]
+        namespace: Option<&'raw str>,
+    },
+}
+
+/// How specific a rule is, see [`ModernPropertyRules`] for the order.
+pub(crate) type Specificity = (u8, bool, bool);
+
+/// A single rule giving a name to the properties it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyRule<'raw> {
+    name: &'raw str,
+    values: Option<EnumProperty<'raw>>,
+    property: Option<&'raw str>,
+    blocks: Vec<BlockPattern<'raw>>,
+    namespace: Option<&'raw str>,
+}
+
+impl<'raw> PropertyRule<'raw> {
+    const FLAT: Specificity = (0, false, true);
+
+    /// Constructs a rule only matching on values.
+    pub fn flat(name: &'raw str, values: EnumProperty<'raw>) -> Self {
         Self {
-            rule_data: other.into_iter().map(|(name, values)| (values, name)).collect(),
+            name,
+            values: Some(values),
+            property: None,
+            blocks: Vec::new(),
+            namespace: None,
+        }
+    }
+
+    /// The name given to the matching properties.
+    pub fn name(&self) -> &'raw str { self.name }
+
+    /// The values this rule matches, if any.
+    pub fn values(&self) -> Option<&EnumProperty<'raw>> { self.values.as_ref() }
+
+    /// Whether this rule only matches on values.
+    pub fn is_flat(&self) -> bool { self.flat_values().is_some() }
+
+    fn flat_values(&self) -> Option<&EnumProperty<'raw>> {
+        match self {
+            PropertyRule {
+                values: Some(values),
+                property: None,
+                namespace: None,
+                blocks,
+                ..
+            } if blocks.is_empty() => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns how specific the match is if this rule matches the property
+    /// of the block.
+    pub(crate) fn matches(&self, block: &Identifier, name: &str, values: &EnumProperty) -> Option<Specificity> {
+        if self.values.as_ref().is_some_and(|own| own != values) || self.property.is_some_and(|own| own != name) {
+            return None;
         }
+        if self.namespace.is_some_and(|namespace| namespace != block.namespace()) {
+            return None;
+        }
+        let scope = if self.blocks.is_empty() {
+            u8::from(self.namespace.is_some())
+        } else {
+            self.blocks.iter().filter_map(|pattern| pattern.matches(block)).max()?
+        };
+        Some((scope, self.property.is_some(), self.values.is_some()))
+    }
+}
+
+impl<'raw> Serialize for PropertyRule<'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if let Some(values) = self.flat_values() {
+            return values.serialize(serializer);
+        }
+        let mut rule = serializer.serialize_struct("PropertyRule", 4)?;
+        if let Some(values) = &self.values {
+            rule.serialize_field("values", values)?;
+        }
+        if let Some(property) = self.property {
+            rule.serialize_field("property", property)?;
+        }
+        if !self.blocks.is_empty() {
+            rule.serialize_field("blocks", &self.blocks)?;
+        }
+        if let Some(namespace) = self.namespace {
+            rule.serialize_field("namespace", namespace)?;
+        }
+        rule.end()
+    }
+}
+
+/// Selects blocks by identifier or by a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockPattern<'raw> {
+    Block(Identifier<'raw>),
+    Pattern(&'raw str),
+}
+
+impl<'raw> BlockPattern<'raw> {
+    /// Returns how specific the match is if the block matches, an exact
+    /// identifier being more specific than a pattern.
+    pub fn matches(&self, block: &Identifier) -> Option<u8> {
+        match self {
+            BlockPattern::Block(identifier) => (identifier == block).then_some(3),
+            BlockPattern::Pattern(pattern) => {
+                let matched = match pattern.contains(':') {
+                    true => glob_matches(pattern, &block.to_string()),
+                    false => glob_matches(pattern, block.location()),
+                };
+                matched.then_some(2)
+            },
+        }
+    }
+}
+
+impl<'raw, 'de: 'raw> Deserialize<'de> for BlockPattern<'raw> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let pattern = <&'de str as Deserialize>::deserialize(deserializer)?;
+        if pattern.contains('*') {
+            Ok(BlockPattern::Pattern(pattern))
+        } else {
+            Identifier::try_from(pattern)
+                .map(BlockPattern::Block)
+                .map_err(|_| D::Error::custom(format!("invalid block identifier \"{}\"", pattern)))
+        }
+    }
+}
+
+impl<'raw> Serialize for BlockPattern<'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            BlockPattern::Block(identifier) => identifier.serialize(serializer),
+            BlockPattern::Pattern(pattern) => serializer.serialize_str(pattern),
+        }
+    }
+}
+
+/// Matches `text` against a pattern where `*` matches any characters.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match text.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts: Vec<_> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_matches;
+    use crate::blocks::raw::property::{EnumProperty, PropertyKind};
+    use crate::util::identifier::Identifier;
+
+    #[test]
+    fn test_glob() {
+        assert!(glob_matches("*_stairs", "oak_stairs"));
+        assert!(!glob_matches("*_stairs", "oak_stairs_top"));
+        assert!(glob_matches("oak_*", "oak_stairs"));
+        assert!(glob_matches("*_*", "oak_stairs"));
+        assert!(glob_matches("minecraft:*_stairs", "minecraft:oak_stairs"));
+        assert!(!glob_matches("a*b*c", "abca"));
+        assert!(glob_matches("stone", "stone"));
+        assert!(!glob_matches("stone", "stones"));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let rules = crate::load_rules(
+            r#"{
+                "horizontal_facing": ["north", "south", "west", "east"],
+                "stairs_facing": { "values": ["north", "south", "west", "east"], "blocks": ["*_stairs"] },
+                "oak_stairs_facing": { "property": "facing", "blocks": ["oak_stairs"] },
+                "modded_facing": { "values": ["north", "south", "west", "east"], "namespace": "mymod" },
+                "modded_stairs_facing": { "property": "facing", "blocks": ["mymod:*_stairs"] }
+            }"#,
+        )
+        .unwrap();
+        let facing = PropertyKind::Enum(EnumProperty::new(&["north", "south", "west", "east"]));
+        let name = |block: &str| rules.transform(&Identifier::try_from(block).unwrap(), "facing", facing.clone()).0;
+
+        assert_eq!(name("minecraft:furnace"), "horizontal_facing");
+        assert_eq!(name("minecraft:stone_stairs"), "stairs_facing");
+        assert_eq!(name("minecraft:oak_stairs"), "oak_stairs_facing");
+        assert_eq!(name("mymod:chest"), "modded_facing");
+        assert_eq!(name("mymod:oak_stairs"), "modded_stairs_facing");
+
+        let other = PropertyKind::Enum(EnumProperty::new(&["up", "down"]));
+        assert_eq!(rules.transform(&Identifier::from_location("stone_stairs"), "facing", other).0, "facing");
+
+        let values = PropertyKind::Enum(EnumProperty::new(&["a", "b"]));
+        let stone = Identifier::from_location("stone");
+        let flat = crate::load_rules(r#"{ "first": ["a", "b"], "second": ["a", "b"] }"#).unwrap();
+        assert_eq!(flat.transform(&stone, "ab", values.clone()).0, "first");
+        let scoped =
+            crate::load_rules(r#"{ "first": { "values": ["a", "b"], "blocks": ["stone"] }, "second": { "values": ["a", "b"], "blocks": ["stone"] } }"#)
+                .unwrap();
+        assert_eq!(scoped.transform(&stone, "ab", values).0, "first");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = r#"{"flat":["a","b"],"scoped":{"values":["c"],"property":"kind","blocks":["minecraft:stone","*_slab"],"namespace":"minecraft"}}"#;
+        let rules = crate::load_rules(data).unwrap();
+        assert_eq!(serde_json::to_string(&rules).unwrap(), data);
+        assert!(crate::load_rules(r#"{"invalid": {"blocks": ["stone"]}}"#).is_err());
     }
 }
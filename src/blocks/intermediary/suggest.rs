@@ -0,0 +1,395 @@
+use ahash::{AHashMap, AHashSet};
+use serde::de::{DeserializeSeed, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+use super::collisions::{CollisionList, CollisionRuleProvider, RawBlockData};
+use super::rules::{ModernPropertyRules, PropertyRule};
+use crate::blocks::raw::property::{EnumProperty, PropertyKind};
+use crate::util::identifier::Identifier;
+
+/// A rules file suggested to resolve the name collisions in raw data.
+///
+/// The rules that were already present are kept as-is and come first, the
+/// suggested rules are appended after them.
+#[derive(Debug)]
+pub struct SuggestedRules<'raw> {
+    existing: Vec<PropertyRule<'raw>>,
+    suggested: Vec<(String, EnumProperty<'raw>)>,
+}
+
+impl<'raw> SuggestedRules<'raw> {
+    /// Constructs a new `SuggestedRules` keeping all of the existing rules.
+    pub fn new(rules: Option<&ModernPropertyRules<'raw>>, suggested: Vec<(String, EnumProperty<'raw>)>) -> Self {
+        let existing = rules.map(|rules| rules.iter().cloned().collect()).unwrap_or_default();
+        Self {
+            existing,
+            suggested,
+        }
+    }
+
+    /// The rules that were already present.
+    pub fn existing(&self) -> &[PropertyRule<'raw>] { &self.existing }
+
+    /// The newly suggested rules.
+    pub fn suggested(&self) -> &[(String, EnumProperty<'raw>)] { &self.suggested }
+}
+
+impl<'raw> Serialize for SuggestedRules<'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.existing.len() + self.suggested.len()))?;
+        for rule in &self.existing {
+            map.serialize_entry(rule.name(), rule)?;
+        }
+        for (name, values) in &self.suggested {
+            map.serialize_entry(name, values)?;
+        }
+        map.end()
+    }
+}
+
+/// Which blocks use the properties involved in name collisions.
+#[derive(Debug, Default)]
+pub struct PropertyUsages<'raw> {
+    names: AHashSet<&'raw str>,
+    usages: AHashMap<(&'raw str, EnumProperty<'raw>), Vec<Identifier<'raw>>>,
+}
+
+impl<'raw> PropertyUsages<'raw> {
+    /// Whether any block uses a property with this name.
+    pub fn is_used(&self, name: &str) -> bool { self.names.contains(name) }
+
+    /// The blocks using the given property, in the order of the raw data.
+    pub fn blocks(&self, name: &'raw str, values: &EnumProperty<'raw>) -> &[Identifier<'raw>] {
+        self.usages.get(&(name, values.clone())).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The colliding value sets of a name, the set used by the most blocks
+    /// comes first.
+    pub fn variants<'a>(&self, collisions: &'a CollisionList<'raw>, name: &'raw str) -> Vec<&'a EnumProperty<'raw>> {
+        let mut variants: Vec<_> = collisions.by_name().get(name).into_iter().flatten().collect();
+        variants.sort_by(|a, b| {
+            let (a_count, b_count) = (self.blocks(name, a).len(), self.blocks(name, b).len());
+            b_count.cmp(&a_count).then_with(|| a.fields().cmp(b.fields()))
+        });
+        variants
+    }
+}
+
+/// Collects the [`PropertyUsages`] of the collisions in a [`CollisionList`].
+pub struct UsageCollector<'a, 'raw> {
+    rules: Option<&'a ModernPropertyRules<'raw>>,
+    collisions: &'a CollisionList<'raw>,
+}
+
+impl<'a, 'raw> UsageCollector<'a, 'raw> {
+    /// Constructs a new `UsageCollector`, `rules` should be the same rules the
+    /// collisions were checked with.
+    pub fn new(rules: Option<&'a ModernPropertyRules<'raw>>, collisions: &'a CollisionList<'raw>) -> Self { Self { rules, collisions } }
+}
+
+impl<'a, 'raw, 'de: 'raw> Visitor<'de> for UsageCollector<'a, 'raw> {
+    type Value = PropertyUsages<'raw>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result { formatter.write_str("a 1.13+ minecraft-generated block list") }
+
+    /// Collect all property names and which blocks use the colliding
+    /// properties
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let transformer = CollisionRuleProvider::new(self.rules);
+        let mut usages = PropertyUsages::default();
+
+        while let Some((identifier, data)) = map.next_entry::<Identifier<'de>, RawBlockData<'de>>()? {
+            for (name, property) in transformer.transform(&identifier, data.properties) {
+                usages.names.insert(name);
+                if let PropertyKind::Enum(property) = property {
+                    if self.collisions.by_name().contains_key(name) {
+                        usages.usages.entry((name, property)).or_default().push(identifier);
+                    }
+                }
+            }
+        }
+
+        Ok(usages)
+    }
+}
+
+impl<'a, 'raw, 'de: 'raw> DeserializeSeed<'de> for UsageCollector<'a, 'raw> {
+    type Value = PropertyUsages<'raw>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+/// Suggests rules for every name collision in a [`CollisionList`].
+///
+/// For every colliding name, one set of values keeps the original name. This
+/// is the set that is already named by the existing rules or otherwise the set
+/// used by the most blocks. The other sets get a new name prefixed by what the
+/// blocks using them have in common, e.g. `hopper_facing` or `stairs_shape`.
+pub struct RuleSuggester<'a, 'raw> {
+    rules: Option<&'a ModernPropertyRules<'raw>>,
+    collisions: &'a CollisionList<'raw>,
+    usages: &'a PropertyUsages<'raw>,
+}
+
+impl<'a, 'raw> RuleSuggester<'a, 'raw> {
+    /// Constructs a new `RuleSuggester`, `rules` should be the same rules the
+    /// collisions were checked with.
+    pub fn new(rules: Option<&'a ModernPropertyRules<'raw>>, collisions: &'a CollisionList<'raw>, usages: &'a PropertyUsages<'raw>) -> Self {
+        Self {
+            rules,
+            collisions,
+            usages,
+        }
+    }
+
+    /// Suggests a name for the given values, avoiding the names in `taken`.
+    pub fn suggest_name(&self, name: &'raw str, values: &EnumProperty<'raw>, taken: &AHashSet<String>) -> String {
+        let base = match common_words(self.usages.blocks(name, values)) {
+            Some(words) => format!("{}_{}", words, name),
+            None => format!("{}_{}", name, values.fields().len()),
+        };
+        let mut candidate = base.clone();
+        let mut counter = 2;
+        while taken.contains(&candidate) || self.usages.is_used(&candidate) {
+            candidate = format!("{}_{}", base, counter);
+            counter += 1;
+        }
+        candidate
+    }
+
+    /// Suggests rules resolving every name collision.
+    pub fn suggest(&self) -> SuggestedRules<'raw> {
+        let mut taken: AHashSet<String> = self
+            .rules
+            .into_iter()
+            .flat_map(|rules| rules.iter())
+            .map(|rule| rule.name().to_string())
+            .collect();
+
+        let mut colliding: Vec<_> = self.collisions.by_name().keys().copied().collect();
+        colliding.sort_unstable();
+
+        let mut suggested = Vec::new();
+        for name in colliding {
+            let variants = self.usages.variants(self.collisions, name);
+            let named: Vec<_> = variants.iter().copied().filter(|values| self.is_named(values)).collect();
+            let kept = if named.is_empty() {
+                vec![variants[0]]
+            } else {
+                named
+            };
+
+            for values in variants {
+                if kept.contains(&values) {
+                    continue;
+                }
+                let candidate = self.suggest_name(name, values, &taken);
+                taken.insert(candidate.clone());
+                suggested.push((candidate, values.clone()));
+            }
+        }
+
+        SuggestedRules::new(self.rules, suggested)
+    }
+
+    /// Whether the existing rules already name these values.
+    pub fn is_named(&self, values: &EnumProperty<'raw>) -> bool {
+        self.rules
+            .is_some_and(|rules| rules.iter().any(|rule| rule.values() == Some(values)))
+    }
+}
+
+/// Why a name chosen for a set of colliding values was rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum NameError {
+    #[error("A property name can only contain lowercase letters, digits and underscores")]
+    Invalid,
+    #[error("\"{0}\" is already kept by another set of values")]
+    OriginalKept(String),
+    #[error("\"{0}\" is already used by another property")]
+    Taken(String),
+}
+
+/// Keeps track of the names chosen for every set of values involved in a
+/// name collision, e.g. when prompting for them.
+///
+/// One set of values per colliding name can keep the original name, every
+/// other name can only be chosen once and must not be used by any property.
+pub struct NameChooser<'a, 'raw> {
+    suggester: RuleSuggester<'a, 'raw>,
+    taken: AHashSet<String>,
+    chosen: Vec<(String, EnumProperty<'raw>)>,
+    /// Whether the original name of the current collision is kept already
+    original_kept: bool,
+}
+
+impl<'a, 'raw> NameChooser<'a, 'raw> {
+    /// Constructs a new `NameChooser`, `rules` should be the same rules the
+    /// collisions were checked with.
+    pub fn new(rules: Option<&'a ModernPropertyRules<'raw>>, collisions: &'a CollisionList<'raw>, usages: &'a PropertyUsages<'raw>) -> Self {
+        let taken = rules
+            .into_iter()
+            .flat_map(|rules| rules.iter())
+            .map(|rule| rule.name().to_string())
+            .collect();
+        Self {
+            suggester: RuleSuggester::new(rules, collisions, usages),
+            taken,
+            chosen: Vec::new(),
+            original_kept: false,
+        }
+    }
+
+    /// Starts choosing names for the sets of values colliding on `name`,
+    /// returning them in the order they should be named in.
+    pub fn start(&mut self, name: &'raw str) -> Vec<&'a EnumProperty<'raw>> {
+        let variants = self.suggester.usages.variants(self.suggester.collisions, name);
+        self.original_kept = variants.iter().any(|values| self.is_named(values));
+        variants
+    }
+
+    /// Whether the existing rules already name these values, they don't
+    /// need a new name then.
+    pub fn is_named(&self, values: &EnumProperty<'raw>) -> bool { self.suggester.is_named(values) }
+
+    /// The name to suggest for a set of values, which is the original name
+    /// as long as no other set of values keeps it.
+    pub fn default_name(&self, name: &'raw str, values: &EnumProperty<'raw>) -> String {
+        if self.original_kept {
+            self.suggester.suggest_name(name, values, &self.taken)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Chooses `answer` as the name for a set of values colliding on `name`.
+    pub fn choose(&mut self, name: &str, values: &EnumProperty<'raw>, answer: &str) -> Result<(), NameError> {
+        if answer.is_empty() || !answer.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+            Err(NameError::Invalid)
+        } else if answer == name {
+            if self.original_kept {
+                return Err(NameError::OriginalKept(name.to_string()));
+            }
+            self.original_kept = true;
+            Ok(())
+        } else if self.taken.contains(answer) || self.suggester.usages.is_used(answer) {
+            Err(NameError::Taken(answer.to_string()))
+        } else {
+            self.taken.insert(answer.to_string());
+            self.chosen.push((answer.to_string(), values.clone()));
+            Ok(())
+        }
+    }
+
+    /// The rules resulting from the chosen names.
+    pub fn finish(self) -> SuggestedRules<'raw> { SuggestedRules::new(self.suggester.rules, self.chosen) }
+}
+
+/// Finds what the names of the blocks have in common, either the last or the
+/// first words of their location.
+fn common_words(blocks: &[Identifier]) -> Option<String> {
+    let (first, rest) = blocks.split_first()?;
+    let first: Vec<_> = first.location().split('_').collect();
+    let words: Vec<Vec<_>> = rest.iter().map(|block| block.location().split('_').collect()).collect();
+
+    let suffix = (0..first.len())
+        .take_while(|&i| {
+            words
+                .iter()
+                .all(|other| other.len() > i && other[other.len() - 1 - i] == first[first.len() - 1 - i])
+        })
+        .count();
+    if suffix > 0 {
+        return Some(first[first.len() - suffix..].join("_"));
+    }
+    let prefix = (0..first.len())
+        .take_while(|&i| words.iter().all(|other| other.get(i) == Some(&first[i])))
+        .count();
+    if prefix > 0 {
+        return Some(first[..prefix].join("_"));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{common_words, NameChooser, NameError};
+    use crate::util::identifier::Identifier;
+
+    const BLOCKS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/blocks.json"));
+
+    #[test]
+    fn test_common_words() {
+        let blocks = |locations: &[&'static str]| {
+            locations
+                .iter()
+                .map(|location| Identifier::from_location(location))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(common_words(&blocks(&["hopper"])).as_deref(), Some("hopper"));
+        assert_eq!(common_words(&blocks(&["oak_stairs", "stone_brick_stairs"])).as_deref(), Some("stairs"));
+        assert_eq!(common_words(&blocks(&["oak_log", "oak_wood"])).as_deref(), Some("oak"));
+        assert_eq!(common_words(&blocks(&["chest", "furnace"])), None);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let collisions = crate::check_collisions(BLOCKS, None).unwrap();
+        let suggested = crate::suggest_rules(BLOCKS, None, &collisions).unwrap();
+        assert_eq!(serde_json::to_value(&suggested).unwrap(), json!({"oak_stairs_facing": ["north", "south", "west", "east"]}));
+
+        let data = serde_json::to_string(&suggested).unwrap();
+        let rules = crate::load_rules(&data).unwrap();
+        assert!(!crate::check_collisions(BLOCKS, Some(&rules)).unwrap().should_exit());
+    }
+
+    #[test]
+    fn test_name_chooser() {
+        let collisions = crate::check_collisions(BLOCKS, None).unwrap();
+        let usages = crate::collect_usages(BLOCKS, None, &collisions).unwrap();
+        let mut chooser = NameChooser::new(None, &collisions, &usages);
+
+        let variants = chooser.start("facing");
+        assert_eq!(variants.len(), 2);
+        assert_eq!(chooser.default_name("facing", variants[0]), "facing");
+        assert_eq!(chooser.choose("facing", variants[0], "Facing"), Err(NameError::Invalid));
+        assert_eq!(chooser.choose("facing", variants[0], "facing"), Ok(()));
+
+        assert_eq!(chooser.default_name("facing", variants[1]), "oak_stairs_facing");
+        assert_eq!(chooser.choose("facing", variants[1], "facing"), Err(NameError::OriginalKept("facing".to_string())));
+        assert_eq!(chooser.choose("facing", variants[1], "half"), Err(NameError::Taken("half".to_string())));
+        assert_eq!(chooser.choose("facing", variants[1], "stairs_facing"), Ok(()));
+
+        let suggested = serde_json::to_value(chooser.finish()).unwrap();
+        assert_eq!(suggested, json!({"stairs_facing": ["north", "south", "west", "east"]}));
+    }
+
+    #[test]
+    fn test_suggest_keeps_existing() {
+        let rules = crate::load_rules(r#"{"facing": ["down", "north", "south", "west", "east"]}"#).unwrap();
+        let collisions = crate::check_collisions(BLOCKS, Some(&rules)).unwrap();
+        let suggested = crate::suggest_rules(BLOCKS, Some(&rules), &collisions).unwrap();
+        let suggested = serde_json::to_value(&suggested).unwrap();
+        let names: Vec<_> = suggested.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(names[0], "facing");
+        assert_eq!(suggested["facing"], json!(["down", "north", "south", "west", "east"]));
+        assert_eq!(names.len(), 2);
+        assert_eq!(suggested[&names[1]], json!(["north", "south", "west", "east"]));
+    }
+}
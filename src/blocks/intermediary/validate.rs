@@ -0,0 +1,306 @@
+use std::fmt::{Display, Formatter};
+
+use ahash::{AHashMap, AHashSet, RandomState};
+use hashlink::LinkedHashMap;
+use serde::de::{DeserializeSeed, Visitor};
+use serde::{Serialize, Serializer};
+
+use super::collisions::{CollisionRuleProvider, RawBlockData};
+use super::rules::{ModernPropertyRules, PropertyRule};
+use crate::blocks::raw::property::{EnumProperty, PropertyKind};
+use crate::util::identifier::Identifier;
+
+/// A problem found in a rules file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum RuleIssue<'raw> {
+    /// The same name is specified more than once.
+    DuplicateName {
+        name: &'raw str,
+    },
+    /// The same values are renamed more than once, only the first name is used.
+    DuplicateValues {
+        names: Vec<&'raw str>,
+        values: EnumProperty<'raw>,
+    },
+    /// The rule doesn't match any property in the raw data.
+    Unused {
+        name: &'raw str,
+        rule: PropertyRule<'raw>,
+    },
+    /// The rule matches properties, but more specific rules always take
+    /// precedence.
+    Overridden {
+        name: &'raw str,
+        rule: PropertyRule<'raw>,
+    },
+    /// The new name is already used by a property with other values, this
+    /// results in a name collision.
+    Shadowed {
+        name: &'raw str,
+        rule: PropertyRule<'raw>,
+        #[serde(serialize_with = "serialize_display")]
+        other: PropertyKind<'raw>,
+    },
+    /// Every property the rule applies to already has this name, either in
+    /// the raw data or because of the automatic `type` -> `kind` rename.
+    Redundant {
+        name: &'raw str,
+        rule: PropertyRule<'raw>,
+        renamed_from_type: bool,
+    },
+}
+
+fn serialize_display<S: Serializer, T: Display>(value: &T, serializer: S) -> Result<S::Ok, S::Error> { serializer.collect_str(value) }
+
+impl<'raw> Display for RuleIssue<'raw> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rule = |rule: &PropertyRule| serde_json::to_string(rule).unwrap_or_default();
+        match self {
+            RuleIssue::DuplicateName { name } => write!(f, "\"{}\" is specified more than once", name),
+            RuleIssue::DuplicateValues { names, values } => {
+                write!(f, "{:?} are renamed to {:?}, only the first name is used", values.fields(), names)
+            },
+            RuleIssue::Unused { name, rule: unused } => write!(f, "\"{}\" -> {} is never used", name, rule(unused)),
+            RuleIssue::Overridden {
+                name,
+                rule: overridden,
+            } => {
+                write!(f, "\"{}\" -> {} is always overridden by more specific rules", name, rule(overridden))
+            },
+            RuleIssue::Shadowed {
+                name,
+                rule: shadowed,
+                other,
+            } => {
+                write!(f, "\"{}\" -> {} collides with \"{}\" -> {}", name, rule(shadowed), name, other)
+            },
+            RuleIssue::Redundant {
+                name,
+                rule: redundant,
+                renamed_from_type,
+            } => {
+                write!(f, "\"{}\" -> {} is redundant", name, rule(redundant))?;
+                if *renamed_from_type {
+                    f.write_str(", \"type\" is already renamed to \"kind\"")?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Validates a rules file against raw data from the Minecraft generators.
+pub struct RulesValidator<'a, 'raw> {
+    rules: &'a ModernPropertyRules<'raw>,
+}
+
+impl<'a, 'raw> RulesValidator<'a, 'raw> {
+    /// Constructs a new `RulesValidator` for the given rules.
+    pub fn new(rules: &'a ModernPropertyRules<'raw>) -> Self { Self { rules } }
+
+    /// Returns the issues that can be found without the raw data.
+    fn check_rules(&self) -> Vec<RuleIssue<'raw>> {
+        let mut issues = Vec::new();
+        let mut names = AHashSet::new();
+        let mut values = LinkedHashMap::<&EnumProperty<'raw>, Vec<&'raw str>, RandomState>::default();
+        for rule in self.rules.iter() {
+            let name = rule.name();
+            if !names.insert(name) && !issues.contains(&RuleIssue::DuplicateName { name }) {
+                issues.push(RuleIssue::DuplicateName { name });
+            }
+            if let (true, Some(property)) = (rule.is_flat(), rule.values()) {
+                values.entry(property).or_insert_with(Vec::new).push(name);
+            }
+        }
+        for (property, names) in values {
+            if names.len() > 1 {
+                issues.push(RuleIssue::DuplicateValues {
+                    names,
+                    values: property.clone(),
+                });
+            }
+        }
+        issues
+    }
+}
+
+/// How a single rule is used throughout the raw data.
+#[derive(Default)]
+struct RuleUsage<'raw> {
+    matched: bool,
+    originals: AHashSet<&'raw str>,
+    applied: AHashSet<PropertyKind<'raw>>,
+}
+
+impl<'a, 'raw, 'de: 'raw> Visitor<'de> for RulesValidator<'a, 'raw> {
+    type Value = Vec<RuleIssue<'raw>>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result { formatter.write_str("a 1.13+ minecraft-generated block list") }
+
+    /// Collect how every rule is used and the properties behind every name
+    /// after applying the rules
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let rules: Vec<_> = self.rules.iter().collect();
+        let transformer = CollisionRuleProvider::new(Some(self.rules));
+        let mut usages: Vec<RuleUsage> = rules.iter().map(|_| RuleUsage::default()).collect();
+        let mut by_name = AHashMap::<&'raw str, AHashSet<PropertyKind<'raw>>>::new();
+
+        while let Some((identifier, data)) = map.next_entry::<Identifier<'de>, RawBlockData<'de>>()? {
+            for (&original, property) in &data.properties {
+                if let PropertyKind::Enum(values) = property {
+                    let name = if original == "type" {
+                        "kind"
+                    } else {
+                        original
+                    };
+                    for (rule, usage) in rules.iter().zip(usages.iter_mut()) {
+                        usage.matched |= rule.matches(&identifier, name, values).is_some();
+                    }
+                    if let Some(applied) = self.rules.find(&identifier, name, values) {
+                        let index = rules.iter().position(|rule| std::ptr::eq(*rule, applied)).unwrap_or_default();
+                        usages[index].originals.insert(original);
+                        usages[index].applied.insert(property.clone());
+                    }
+                }
+            }
+            for (name, property) in transformer.transform(&identifier, data.properties) {
+                by_name.entry(name).or_default().insert(property);
+            }
+        }
+
+        let mut issues = self.check_rules();
+        let duplicated: AHashSet<_> = issues
+            .iter()
+            .filter_map(|issue| match issue {
+                RuleIssue::DuplicateValues { values, .. } => Some(values.clone()),
+                _ => None,
+            })
+            .collect();
+        for (rule, usage) in rules.into_iter().zip(usages) {
+            let name = rule.name();
+            if !usage.matched {
+                issues.push(RuleIssue::Unused {
+                    name,
+                    rule: rule.clone(),
+                });
+                continue;
+            }
+            if usage.applied.is_empty() {
+                if !rule.values().is_some_and(|values| rule.is_flat() && duplicated.contains(values)) {
+                    issues.push(RuleIssue::Overridden {
+                        name,
+                        rule: rule.clone(),
+                    });
+                }
+                continue;
+            }
+            if let Some(other) = by_name.get(name).into_iter().flatten().find(|other| !usage.applied.contains(*other)) {
+                issues.push(RuleIssue::Shadowed {
+                    name,
+                    rule: rule.clone(),
+                    other: other.clone(),
+                });
+            }
+            if usage
+                .originals
+                .iter()
+                .all(|&original| original == name || (original == "type" && name == "kind"))
+            {
+                issues.push(RuleIssue::Redundant {
+                    name,
+                    rule: rule.clone(),
+                    renamed_from_type: usage.originals.contains("type"),
+                });
+            }
+        }
+
+        Ok(issues)
+    }
+}
+
+impl<'a, 'raw, 'de: 'raw> DeserializeSeed<'de> for RulesValidator<'a, 'raw> {
+    type Value = Vec<RuleIssue<'raw>>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RuleIssue;
+    use crate::blocks::intermediary::rules::PropertyRule;
+    use crate::blocks::raw::property::{EnumProperty, PropertyKind};
+
+    const BLOCKS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/blocks.json"));
+    const RULES: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rules.json"));
+
+    #[test]
+    fn test_valid_rules() {
+        assert_eq!(crate::validate_rules(BLOCKS, RULES).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_issues() {
+        let rules = r#"{
+            "hopper_facing": ["down", "north", "south", "west", "east"],
+            "hopper_facing": ["down", "up"],
+            "other_facing": ["down", "north", "south", "west", "east"],
+            "kind": ["top", "bottom", "double"],
+            "half": ["top", "bottom"],
+            "powered": ["up", "side", "none"]
+        }"#;
+        let issues = crate::validate_rules(BLOCKS, rules).unwrap();
+
+        let hopper = EnumProperty::new(&["down", "north", "south", "west", "east"]);
+        assert!(issues.contains(&RuleIssue::DuplicateName {
+            name: "hopper_facing"
+        }));
+        assert!(issues.contains(&RuleIssue::DuplicateValues {
+            names: vec!["hopper_facing", "other_facing"],
+            values: hopper,
+        }));
+        assert!(issues.contains(&RuleIssue::Unused {
+            name: "hopper_facing",
+            rule: PropertyRule::flat("hopper_facing", EnumProperty::new(&["down", "up"])),
+        }));
+        assert!(issues.contains(&RuleIssue::Redundant {
+            name: "half",
+            rule: PropertyRule::flat("half", EnumProperty::new(&["top", "bottom"])),
+            renamed_from_type: false,
+        }));
+        assert!(issues.contains(&RuleIssue::Redundant {
+            name: "kind",
+            rule: PropertyRule::flat("kind", EnumProperty::new(&["top", "bottom", "double"])),
+            renamed_from_type: true,
+        }));
+        assert!(issues.contains(&RuleIssue::Shadowed {
+            name: "powered",
+            rule: PropertyRule::flat("powered", EnumProperty::new(&["up", "side", "none"])),
+            other: PropertyKind::Bool,
+        }));
+        assert_eq!(issues.len(), 6);
+    }
+
+    #[test]
+    fn test_overridden() {
+        let rules = r#"{
+            "stairs_half": { "property": "half", "blocks": ["*_stairs"] },
+            "oak_half": { "values": ["top", "bottom"], "blocks": ["oak_stairs"] },
+            "slab_kind": { "property": "kind", "namespace": "minecraft" }
+        }"#;
+        let issues = crate::validate_rules(BLOCKS, rules).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(issues[0], RuleIssue::Overridden {
+            name: "stairs_half",
+            ..
+        }));
+    }
+}
@@ -0,0 +1,243 @@
+//! Blocks of versions before 1.13, identified by a numeric id and a 4-bit
+//! metadata value.
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use ahash::RandomState;
+use hashlink::LinkedHashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::blocks::intermediary::data::ModernBlockList;
+use crate::blocks::intermediary::lookup::LookupError;
+use crate::blocks::intermediary::MetaData;
+use crate::util::blockstate::BlockStateString;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LegacyIdError {
+    #[error("Invalid block id \"{0}\"")]
+    InvalidId(String),
+    #[error("Invalid metadata \"{0}\", expected a value from 0 to 15")]
+    InvalidMeta(String),
+}
+
+/// A legacy block id together with its metadata, written as `id:meta`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LegacyId {
+    pub id: u16,
+    pub meta: u8,
+}
+
+impl LegacyId {
+    pub fn new(id: u16, meta: u8) -> Self { Self { id, meta } }
+
+    /// The combined `id << 4 | meta` value used by the network protocol and
+    /// chunk data.
+    pub fn value(&self) -> u32 { (self.id as u32) << 4 | self.meta as u32 }
+
+    /// Splits a combined `id << 4 | meta` value.
+    pub fn from_value(value: u32) -> Self {
+        Self {
+            id: (value >> 4) as u16,
+            meta: (value & 0xF) as u8,
+        }
+    }
+}
+
+impl Display for LegacyId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}:{}", self.id, self.meta) }
+}
+
+impl FromStr for LegacyId {
+    type Err = LegacyIdError;
+
+    /// Parses `id:meta`, or just `id` for metadata 0
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, meta) = s.split_once(':').unwrap_or((s, "0"));
+        let id = id.trim().parse().map_err(|_| LegacyIdError::InvalidId(id.to_string()))?;
+        let meta = meta
+            .trim()
+            .parse()
+            .ok()
+            .filter(|meta| *meta < 16)
+            .ok_or_else(|| LegacyIdError::InvalidMeta(meta.to_string()))?;
+        Ok(Self { id, meta })
+    }
+}
+
+impl<'de> Deserialize<'de> for LegacyId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let id = <&str as Deserialize>::deserialize(deserializer)?;
+        id.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for LegacyId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// A table of legacy blocks and their flattened blockstates.
+///
+/// ```json
+/// {
+///     "blocks": {
+///         "1:0": "minecraft:stone",
+///         "1:1": "minecraft:granite",
+///         "17:4": "minecraft:oak_log[axis=x]"
+///     }
+/// }
+/// ```
+///
+/// Properties missing in a blockstate take the value of the default
+/// blockstate. Other top-level fields, like the `items` of most published
+/// tables, are ignored.
+#[derive(Debug, Deserialize)]
+pub struct LegacyBlockTable<'raw> {
+    #[serde(borrow)]
+    pub blocks: LinkedHashMap<LegacyId, BlockStateString<'raw>, RandomState>,
+}
+
+/// A legacy block that couldn't be mapped to a flattened blockstate.
+#[derive(Debug, Serialize)]
+pub struct UnmappedLegacyState<'raw> {
+    pub legacy: LegacyId,
+    pub value: u32,
+    pub state: BlockStateString<'raw>,
+    #[serde(serialize_with = "serialize_display")]
+    pub reason: LookupError,
+}
+
+fn serialize_display<S: Serializer, T: Display>(value: &T, serializer: S) -> Result<S::Ok, S::Error> { serializer.collect_str(value) }
+
+/// A mapping from legacy `id << 4 | meta` values to the blockstate ids of a
+/// [`ModernBlockList`].
+#[derive(Debug, Serialize)]
+pub struct LegacyMapping<'raw> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<MetaData<'raw>>,
+    pub states: LinkedHashMap<u32, i32, RandomState>,
+    pub unmapped: Vec<UnmappedLegacyState<'raw>>,
+    /// Metas of the ids in the table that have no entry themselves, these
+    /// can't be mapped either
+    pub missing: Vec<LegacyId>,
+}
+
+impl<'raw> LegacyMapping<'raw> {
+    /// Maps every legacy block of the table, blocks whose blockstate can't be
+    /// found in `data` end up in [`LegacyMapping::unmapped`]. Metas from 0 to
+    /// 15 that the table doesn't list for one of its ids end up in
+    /// [`LegacyMapping::missing`].
+    pub fn new(table: &LegacyBlockTable<'raw>, data: &ModernBlockList<'raw>) -> Result<Self, LookupError> {
+        let index = data.index()?;
+        let mut states = LinkedHashMap::with_hasher(RandomState::default());
+        let mut unmapped = Vec::new();
+        for (legacy, state) in &table.blocks {
+            match index.resolve(state) {
+                Ok(id) => {
+                    states.insert(legacy.value(), id);
+                },
+                Err(reason) => unmapped.push(UnmappedLegacyState {
+                    legacy: *legacy,
+                    value: legacy.value(),
+                    state: state.clone(),
+                    reason,
+                }),
+            }
+        }
+        let missing: Vec<LegacyId> = table
+            .blocks
+            .keys()
+            .map(|legacy| legacy.id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .flat_map(|id| (0..16).map(move |meta| LegacyId::new(id, meta)))
+            .filter(|legacy| !table.blocks.contains_key(legacy))
+            .collect();
+        Ok(Self {
+            to: data.metadata.clone(),
+            states,
+            unmapped,
+            missing,
+        })
+    }
+
+    /// The mapping in the other direction, from blockstate ids to legacy
+    /// values.
+    ///
+    /// Multiple legacy values can map to the same blockstate, in which case
+    /// the lowest value is used.
+    pub fn reverse(&self) -> LinkedHashMap<i32, u32, RandomState> {
+        let mut states: Vec<(i32, u32)> = self.states.iter().map(|(&legacy, &id)| (id, legacy)).collect();
+        states.sort_unstable();
+        states.dedup_by_key(|(id, _)| *id);
+        states.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LegacyBlockTable, LegacyId, LegacyIdError, LegacyMapping};
+    use crate::blocks::intermediary::data::ModernBlockList;
+    use crate::blocks::intermediary::lookup::LookupError;
+
+    #[test]
+    fn test_legacy_id() {
+        assert_eq!("17:4".parse(), Ok(LegacyId::new(17, 4)));
+        assert_eq!("35".parse(), Ok(LegacyId::new(35, 0)));
+        assert_eq!("1:16".parse::<LegacyId>(), Err(LegacyIdError::InvalidMeta("16".to_string())));
+        assert!(matches!("stone:1".parse::<LegacyId>(), Err(LegacyIdError::InvalidId(_))));
+        assert_eq!(LegacyId::new(17, 4).value(), 276);
+        assert_eq!(LegacyId::from_value(276), LegacyId::new(17, 4));
+    }
+
+    #[test]
+    fn test_legacy_mapping() {
+        let data: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "axis": ["x", "y", "z"] },
+                "blocks": {
+                    "minecraft:stone": { "base": 0 },
+                    "minecraft:oak_log": { "properties": { "axis": "axis" }, "base": 1, "default": 2 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let table: LegacyBlockTable = serde_json::from_str(
+            r#"{
+                "blocks": {
+                    "1:0": "minecraft:stone",
+                    "1:1": "minecraft:granite",
+                    "17:0": "minecraft:oak_log",
+                    "17:4": "minecraft:oak_log[axis=x]",
+                    "17:12": "minecraft:oak_log[axis=none]"
+                },
+                "items": {}
+            }"#,
+        )
+        .unwrap();
+
+        let mapping = LegacyMapping::new(&table, &data).unwrap();
+        let states: Vec<(u32, i32)> = mapping.states.iter().map(|(&legacy, &id)| (legacy, id)).collect();
+        assert_eq!(states, vec![(16, 0), (272, 2), (276, 1)]);
+        assert_eq!(mapping.unmapped.len(), 2);
+        assert_eq!(mapping.unmapped[0].legacy, LegacyId::new(1, 1));
+        assert_eq!(mapping.unmapped[0].reason, LookupError::UnknownBlock("minecraft:granite".to_string()));
+        assert!(matches!(mapping.unmapped[1].reason, LookupError::InvalidValue { .. }));
+        assert_eq!(mapping.missing.len(), 2 * 16 - 5);
+        assert_eq!(mapping.missing[..2], [LegacyId::new(1, 2), LegacyId::new(1, 3)]);
+        assert!(!mapping.missing.contains(&LegacyId::new(17, 12)));
+        assert!(mapping.missing.contains(&LegacyId::new(17, 8)));
+
+        let reverse: Vec<(i32, u32)> = mapping.reverse().into_iter().collect();
+        assert_eq!(reverse, vec![(0, 16), (1, 276), (2, 272)]);
+    }
+}
@@ -0,0 +1,250 @@
+use flate2::Crc;
+use thiserror::Error;
+
+use super::StateMapping;
+
+/// The bytes every binary mapping starts with.
+pub const MAGIC: &[u8; 4] = b"MCSM";
+/// The version of the binary format.
+pub const FORMAT_VERSION: u8 = 1;
+/// The size of the header in bytes.
+pub const HEADER_SIZE: usize = 28;
+
+#[derive(Debug, Error)]
+pub enum BinaryError {
+    #[error("The binary mapping needs metadata for both versions")]
+    MissingMetaData,
+    #[error("Blockstate id {0} is negative")]
+    NegativeId(i32),
+    #[error("Blockstate id {id} doesn't fit in {width:?}")]
+    TooLarge {
+        id: u32,
+        width: ArrayWidth,
+    },
+    #[error("The binary mapping is invalid: {0}")]
+    Invalid(&'static str),
+}
+
+/// The size of a single entry of a [`MappingArray`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArrayWidth {
+    U16,
+    U32,
+}
+
+impl ArrayWidth {
+    /// The size of an entry in bytes.
+    pub fn bytes(&self) -> usize {
+        match self {
+            ArrayWidth::U16 => 2,
+            ArrayWidth::U32 => 4,
+        }
+    }
+
+    /// The value marking a blockstate without a counterpart.
+    pub fn unmapped(&self) -> u32 {
+        match self {
+            ArrayWidth::U16 => u16::MAX as u32,
+            ArrayWidth::U32 => u32::MAX,
+        }
+    }
+}
+
+/// A [`StateMapping`] as a flat array indexed by source blockstate id.
+///
+/// The binary form is little-endian and starts with a header of
+/// [`HEADER_SIZE`] bytes:
+///
+/// | offset | type      | content                                 |
+/// |--------|-----------|-----------------------------------------|
+/// | 0      | `[u8; 4]` | [`MAGIC`]                               |
+/// | 4      | `u8`      | [`FORMAT_VERSION`]                      |
+/// | 5      | `u8`      | size of an entry in bytes, 2 or 4       |
+/// | 6      | `u16`     | reserved, always 0                      |
+/// | 8      | `i32`     | [`MetaData::id`] of the source version  |
+/// | 12     | `i32`     | [`MetaData::id`] of the target version  |
+/// | 16     | `u32`     | amount of entries                       |
+/// | 20     | `u32`     | amount of unmapped entries              |
+/// | 24     | `u32`     | CRC-32 checksum of the entries          |
+///
+/// The entries follow directly after the header, unmapped blockstates are
+/// marked by the maximum value of the entry type.
+///
+/// The same format is used for registry id mappings, see
+/// [`RegistryIdMapping::array`], with protocol ids in place of blockstate
+/// ids.
+///
+/// [`MetaData::id`]: crate::blocks::intermediary::MetaData::id
+/// [`RegistryIdMapping::array`]: crate::registries::mapping::RegistryIdMapping::array
+#[derive(Debug, PartialEq, Eq)]
+pub struct MappingArray {
+    pub from: i32,
+    pub to: i32,
+    pub states: Vec<Option<u32>>,
+}
+
+impl MappingArray {
+    /// Flattens a mapping, both versions need metadata.
+    pub fn new(mapping: &StateMapping) -> Result<Self, BinaryError> {
+        let (from, to) = match (&mapping.from, &mapping.to) {
+            (Some(from), Some(to)) => (from.id, to.id),
+            _ => return Err(BinaryError::MissingMetaData),
+        };
+
+        let ids = mapping.states.keys().chain(mapping.unmapped.iter().map(|state| &state.id));
+        let count = ids.map(|&id| id + 1).max().unwrap_or(0);
+        let mut states = vec![None; count as usize];
+        for (&from, &to) in &mapping.states {
+            let from = usize::try_from(from).map_err(|_| BinaryError::NegativeId(from))?;
+            states[from] = Some(u32::try_from(to).map_err(|_| BinaryError::NegativeId(to))?);
+        }
+        Ok(Self { from, to, states })
+    }
+
+    /// Translates a source id, returns `None` if it isn't mapped.
+    pub fn get(&self, id: usize) -> Option<u32> { self.states.get(id).copied().flatten() }
+
+    /// The smallest width fitting every target id.
+    pub fn width(&self) -> ArrayWidth {
+        let max = self.states.iter().flatten().copied().max().unwrap_or(0);
+        if max < ArrayWidth::U16.unmapped() {
+            ArrayWidth::U16
+        } else {
+            ArrayWidth::U32
+        }
+    }
+
+    /// Encodes this mapping in the binary format.
+    pub fn to_bytes(&self, width: ArrayWidth) -> Result<Vec<u8>, BinaryError> {
+        let mut entries = Vec::with_capacity(self.states.len() * width.bytes());
+        for state in &self.states {
+            let value = state.unwrap_or(width.unmapped());
+            match width {
+                ArrayWidth::U16 if value > u16::MAX as u32 || (state.is_some() && value == width.unmapped()) => {
+                    return Err(BinaryError::TooLarge { id: value, width })
+                },
+                ArrayWidth::U16 => entries.extend_from_slice(&(value as u16).to_le_bytes()),
+                ArrayWidth::U32 => entries.extend_from_slice(&value.to_le_bytes()),
+            }
+        }
+        let mut crc = Crc::new();
+        crc.update(&entries);
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + entries.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        bytes.push(width.bytes() as u8);
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&self.from.to_le_bytes());
+        bytes.extend_from_slice(&self.to.to_le_bytes());
+        bytes.extend_from_slice(&(self.states.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.states.iter().filter(|state| state.is_none()).count() as u32).to_le_bytes());
+        bytes.extend_from_slice(&crc.sum().to_le_bytes());
+        bytes.extend_from_slice(&entries);
+        Ok(bytes)
+    }
+
+    /// Decodes a mapping in the binary format, verifying the checksum and the
+    /// amount of unmapped entries.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BinaryError> {
+        if bytes.len() < HEADER_SIZE || &bytes[..4] != MAGIC {
+            return Err(BinaryError::Invalid("missing header"));
+        }
+        if bytes[4] != FORMAT_VERSION {
+            return Err(BinaryError::Invalid("unsupported format version"));
+        }
+        let width = match bytes[5] {
+            2 => ArrayWidth::U16,
+            4 => ArrayWidth::U32,
+            _ => return Err(BinaryError::Invalid("unsupported entry size")),
+        };
+        if bytes[6..8] != [0, 0] {
+            return Err(BinaryError::Invalid("reserved field isn't 0"));
+        }
+        let read = |offset: usize| [bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]];
+        let count = u32::from_le_bytes(read(16)) as usize;
+        let entries = &bytes[HEADER_SIZE..];
+        if entries.len() != count * width.bytes() {
+            return Err(BinaryError::Invalid("wrong amount of entries"));
+        }
+        let mut crc = Crc::new();
+        crc.update(entries);
+        if crc.sum() != u32::from_le_bytes(read(24)) {
+            return Err(BinaryError::Invalid("checksum mismatch"));
+        }
+
+        let states: Vec<_> = entries
+            .chunks_exact(width.bytes())
+            .map(|entry| {
+                let value = match width {
+                    ArrayWidth::U16 => u16::from_le_bytes([entry[0], entry[1]]) as u32,
+                    ArrayWidth::U32 => u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]),
+                };
+                (value != width.unmapped()).then_some(value)
+            })
+            .collect();
+        if states.iter().filter(|state| state.is_none()).count() != u32::from_le_bytes(read(20)) as usize {
+            return Err(BinaryError::Invalid("wrong amount of unmapped entries"));
+        }
+        Ok(Self {
+            from: i32::from_le_bytes(read(8)),
+            to: i32::from_le_bytes(read(12)),
+            states,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrayWidth, BinaryError, MappingArray, HEADER_SIZE};
+    use crate::blocks::intermediary::data::ModernBlockList;
+    use crate::blocks::mapping::StateMapper;
+
+    #[test]
+    fn test_binary() {
+        let from: ModernBlockList = serde_json::from_str(
+            r#"{
+                "metadata": { "id": 1 },
+                "properties": {},
+                "blocks": {
+                    "minecraft:stone": { "base": 0 },
+                    "minecraft:grass_path": { "base": 1 },
+                    "minecraft:dirt": { "base": 2 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let to: ModernBlockList = serde_json::from_str(
+            r#"{
+                "metadata": { "id": 2 },
+                "properties": {},
+                "blocks": {
+                    "minecraft:stone": { "base": 0 },
+                    "minecraft:copper_block": { "base": 1 },
+                    "minecraft:dirt": { "base": 2 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mapping = StateMapper::new(&from, &to, None).map().unwrap();
+        let array = MappingArray::new(&mapping).unwrap();
+        assert_eq!(array.states, vec![Some(0), None, Some(2)]);
+        assert_eq!(array.width(), ArrayWidth::U16);
+
+        let bytes = array.to_bytes(ArrayWidth::U16).unwrap();
+        assert_eq!(bytes.len(), HEADER_SIZE + 6);
+        assert_eq!(&bytes[HEADER_SIZE..], &[0, 0, 0xFF, 0xFF, 2, 0]);
+        assert_eq!(MappingArray::from_bytes(&bytes).unwrap(), array);
+        assert_eq!(MappingArray::from_bytes(&array.to_bytes(ArrayWidth::U32).unwrap()).unwrap(), array);
+
+        let corrupt = |offset: usize| {
+            let mut corrupted = bytes.clone();
+            corrupted[offset] ^= 2;
+            MappingArray::from_bytes(&corrupted)
+        };
+        assert!(matches!(corrupt(HEADER_SIZE), Err(BinaryError::Invalid("checksum mismatch"))));
+        assert!(matches!(corrupt(6), Err(BinaryError::Invalid("reserved field isn't 0"))));
+        assert!(matches!(corrupt(20), Err(BinaryError::Invalid("wrong amount of unmapped entries"))));
+    }
+}
@@ -0,0 +1,639 @@
+use std::borrow::Cow;
+
+use ahash::{AHashMap, RandomState};
+use hashlink::LinkedHashMap;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::blocks::intermediary::data::{value_indices, ModernBlockData, ModernBlockList, PropertyList, PropertyValues};
+use crate::blocks::intermediary::MetaData;
+use crate::util::blockstate::BlockStateString;
+use crate::util::identifier::Identifier;
+
+mod binary;
+mod ranges;
+mod report;
+mod rules;
+
+pub use binary::{ArrayWidth, BinaryError, MappingArray, FORMAT_VERSION, HEADER_SIZE, MAGIC};
+pub use ranges::{IdRange, RangeTable};
+pub use report::{CollapsedStates, Lossiness, LossinessCounts, LossinessReport};
+pub use rules::{BlockRename, FallbackRules, MappingRules, ValueRule};
+
+/// A blockstate mapping from one version of block data to another.
+///
+/// Every blockstate of the source version that has an exact counterpart in
+/// the target version is listed in [`StateMapping::states`]. Blockstates
+/// resolved using a fallback are listed there as well, and also in
+/// [`StateMapping::fallbacks`] together with the strategy that resolved them.
+/// All the others end up in [`StateMapping::unmapped`] together with the reason
+/// why they couldn't be mapped.
+#[derive(Debug, Serialize)]
+pub struct StateMapping<'raw> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<MetaData<'raw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<MetaData<'raw>>,
+    pub states: LinkedHashMap<i32, i32, RandomState>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<FallbackState<'raw>>,
+    pub unmapped: Vec<UnmappedState<'raw>>,
+}
+
+/// A blockstate of the source version without an exact counterpart.
+#[derive(Debug, Serialize)]
+pub struct UnmappedState<'raw> {
+    pub id: i32,
+    pub block: Identifier<'raw>,
+    #[serde(skip_serializing_if = "LinkedHashMap::is_empty")]
+    pub properties: LinkedHashMap<&'raw str, Cow<'raw, str>, RandomState>,
+    pub reason: UnmappedReason<'raw>,
+}
+
+/// A blockstate of the source version without an exact counterpart that was
+/// resolved using a fallback.
+#[derive(Debug, Serialize)]
+pub struct FallbackState<'raw> {
+    pub id: i32,
+    pub block: Identifier<'raw>,
+    #[serde(skip_serializing_if = "LinkedHashMap::is_empty")]
+    pub properties: LinkedHashMap<&'raw str, Cow<'raw, str>, RandomState>,
+    pub reason: UnmappedReason<'raw>,
+    pub strategy: FallbackStrategy,
+    pub target: i32,
+}
+
+/// The fallback used to resolve a blockstate, see [`FallbackRules`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackStrategy {
+    /// The fallback specified for the block
+    Explicit,
+    /// The same block, keeping as many properties as possible
+    Partial,
+    /// The catch-all fallback
+    CatchAll,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "property")]
+pub enum UnmappedReason<'raw> {
+    /// The block doesn't exist in the target version
+    MissingBlock,
+    /// The target block has a property the source block doesn't have
+    MissingProperty(&'raw str),
+    /// The target block doesn't accept the value of this property
+    InvalidValue(&'raw str),
+}
+
+#[derive(Debug, Error)]
+pub enum MappingError {
+    #[error("Unknown property \"{property}\" for block \"{block}\"")]
+    UnknownProperty {
+        block: String,
+        property: String,
+    },
+    #[error("Value \"{value}\" of property \"{property}\" maps to a list that doesn't start with a value of block \"{block}\"")]
+    InvalidValueList {
+        block: String,
+        property: String,
+        value: String,
+    },
+    #[error("Invalid fallback \"{fallback}\": {reason}")]
+    InvalidFallback {
+        fallback: String,
+        reason: String,
+    },
+}
+
+/// Generates a [`StateMapping`] between two versions of block data.
+///
+/// Blocks are matched by their identifier and properties are matched by their
+/// name and value, unless [`MappingRules`] say otherwise. Properties of the
+/// source block that don't exist in the target block are dropped.
+pub struct StateMapper<'a, 'raw> {
+    from: &'a ModernBlockList<'raw>,
+    to: &'a ModernBlockList<'raw>,
+    rules: Option<&'a MappingRules<'raw>>,
+}
+
+impl<'a, 'raw> StateMapper<'a, 'raw> {
+    pub fn new(from: &'a ModernBlockList<'raw>, to: &'a ModernBlockList<'raw>, rules: Option<&'a MappingRules<'raw>>) -> Self { Self { from, to, rules } }
+
+    pub fn map(&self) -> Result<StateMapping<'raw>, MappingError> {
+        let mut states = LinkedHashMap::with_hasher(RandomState::default());
+        let mut fallbacks = Vec::new();
+        let mut unmapped = Vec::new();
+
+        let mut targets = AHashMap::new();
+        for (identifier, block) in &self.from.blocks {
+            let values = resolve(identifier, block, &self.from.properties)?;
+
+            for (offset, indices) in StateIndices::new(&values).enumerate() {
+                let id = block.base_id + offset as i32;
+                let state = || {
+                    values
+                        .iter()
+                        .zip(&indices)
+                        .map(|((name, values), &index)| (*name, values[index].clone()))
+                        .collect()
+                };
+                let rename = self.rules.and_then(|rules| {
+                    rules.block_rename(identifier, |property| {
+                        values
+                            .iter()
+                            .zip(&indices)
+                            .find(|((name, _), _)| *name == property)
+                            .map(|((_, values), &index)| values[index].as_ref())
+                    })
+                });
+                let target_identifier = rename.map_or(identifier, BlockRename::to);
+                let result = match self.target(&mut targets, target_identifier)? {
+                    Some(target) => {
+                        self.check_value_lists(identifier, target_identifier, &target.values)?;
+                        self.target_offset(identifier, rename, &values, &indices, &target.values)
+                            .map(|offset| target.base_id + offset as i32)
+                    },
+                    None => Err(UnmappedReason::MissingBlock),
+                };
+                match result {
+                    Ok(target_id) => {
+                        states.insert(id, target_id);
+                    },
+                    Err(reason) => match self.fallback(&mut targets, identifier, rename, &values, &indices, &reason)? {
+                        Some((target, strategy)) => {
+                            states.insert(id, target);
+                            fallbacks.push(FallbackState {
+                                id,
+                                block: *identifier,
+                                properties: state(),
+                                reason,
+                                strategy,
+                                target,
+                            });
+                        },
+                        None => unmapped.push(UnmappedState {
+                            id,
+                            block: *identifier,
+                            properties: state(),
+                            reason,
+                        }),
+                    },
+                }
+            }
+        }
+
+        Ok(StateMapping {
+            from: self.from.metadata.clone(),
+            to: self.to.metadata.clone(),
+            states,
+            fallbacks,
+            unmapped,
+        })
+    }
+
+    /// Looks up a block of the target version, caching the result.
+    fn target<'c>(
+        &self,
+        targets: &'c mut AHashMap<Identifier<'raw>, Option<Target<'raw>>>,
+        identifier: &Identifier<'raw>,
+    ) -> Result<Option<&'c Target<'raw>>, MappingError> {
+        if !targets.contains_key(identifier) {
+            let target = self
+                .to
+                .blocks
+                .get(identifier)
+                .map(|block| {
+                    resolve(identifier, block, &self.to.properties).map(|values| {
+                        let defaults = value_indices(&values, (block.default_id() - block.base_id) as usize);
+                        Target {
+                            base_id: block.base_id,
+                            values,
+                            defaults,
+                        }
+                    })
+                })
+                .transpose()?;
+            targets.insert(*identifier, target);
+        }
+        Ok(targets[identifier].as_ref())
+    }
+
+    /// Tries the fallbacks of the rules in order, returning the target id and
+    /// the strategy that resolved it.
+    fn fallback(
+        &self,
+        targets: &mut AHashMap<Identifier<'raw>, Option<Target<'raw>>>,
+        identifier: &Identifier<'raw>,
+        rename: Option<&BlockRename<'raw>>,
+        values: &PropertyValues<'raw>,
+        indices: &[usize],
+        reason: &UnmappedReason<'raw>,
+    ) -> Result<Option<(i32, FallbackStrategy)>, MappingError> {
+        let rules = match self.rules {
+            Some(rules) => &rules.fallback,
+            None => return Ok(None),
+        };
+
+        if let Some(fallback) = rules.blocks.get(identifier) {
+            let target = self.fallback_target(targets, fallback)?;
+            self.check_value_lists(identifier, fallback.identifier(), &target.values)?;
+            let fixed = fixed_indices(fallback, target)?;
+            let offset = combine(&target.values, |index, name, target_values| {
+                fixed[index]
+                    .or_else(|| self.target_index(identifier, None, values, indices, name, target_values).ok())
+                    .unwrap_or(target.defaults[index])
+            });
+            return Ok(Some((target.base_id + offset as i32, FallbackStrategy::Explicit)));
+        }
+
+        if rules.partial && !matches!(reason, UnmappedReason::MissingBlock) {
+            if let Some(target) = self.target(targets, rename.map_or(identifier, BlockRename::to))? {
+                let offset = combine(&target.values, |index, name, target_values| {
+                    self.target_index(identifier, rename, values, indices, name, target_values)
+                        .unwrap_or(target.defaults[index])
+                });
+                return Ok(Some((target.base_id + offset as i32, FallbackStrategy::Partial)));
+            }
+        }
+
+        if let Some(fallback) = &rules.default {
+            let target = self.fallback_target(targets, fallback)?;
+            let fixed = fixed_indices(fallback, target)?;
+            let offset = combine(&target.values, |index, _, _| fixed[index].unwrap_or(target.defaults[index]));
+            return Ok(Some((target.base_id + offset as i32, FallbackStrategy::CatchAll)));
+        }
+
+        Ok(None)
+    }
+
+    fn fallback_target<'c>(
+        &self,
+        targets: &'c mut AHashMap<Identifier<'raw>, Option<Target<'raw>>>,
+        fallback: &BlockStateString<'raw>,
+    ) -> Result<&'c Target<'raw>, MappingError> {
+        self.target(targets, fallback.identifier())?
+            .ok_or_else(|| MappingError::InvalidFallback {
+                fallback: fallback.to_string(),
+                reason: "unknown block".to_string(),
+            })
+    }
+
+    /// Makes sure that every list of values the rules map a value of this
+    /// block to starts with a value accepted by the target block `target`.
+    fn check_value_lists(&self, identifier: &Identifier, target: &Identifier, target_values: &PropertyValues<'raw>) -> Result<(), MappingError> {
+        let rules = match self.rules {
+            Some(rules) => rules,
+            None => return Ok(()),
+        };
+        for (name, values) in target_values {
+            let rule = match rules.value_rule(identifier, name) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            for (value, first) in rule.value_lists() {
+                if first.is_none_or(|first| !values.iter().any(|x| x == first)) {
+                    return Err(MappingError::InvalidValueList {
+                        block: target.to_string(),
+                        property: name.to_string(),
+                        value: value.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Calculates the offset of the target blockstate matching the source
+    /// blockstate given by `indices`, translating values using the rules.
+    fn target_offset(
+        &self,
+        identifier: &Identifier,
+        rename: Option<&BlockRename<'raw>>,
+        values: &PropertyValues<'raw>,
+        indices: &[usize],
+        target_values: &PropertyValues<'raw>,
+    ) -> Result<usize, UnmappedReason<'raw>> {
+        let mut offset = 0;
+        for (name, target) in target_values {
+            let index = self.target_index(identifier, rename, values, indices, name, target)?;
+            offset = offset * target.len() + index;
+        }
+        Ok(offset)
+    }
+
+    /// Finds the index of the value of a property of the target block.
+    fn target_index(
+        &self,
+        identifier: &Identifier,
+        rename: Option<&BlockRename<'raw>>,
+        values: &PropertyValues<'raw>,
+        indices: &[usize],
+        name: &'raw str,
+        target: &[Cow<'raw, str>],
+    ) -> Result<usize, UnmappedReason<'raw>> {
+        let rule = self.rules.and_then(|rules| rules.value_rule(identifier, name));
+        let source_name = rule.and_then(ValueRule::property).unwrap_or(name);
+        let source_value = values
+            .iter()
+            .zip(indices)
+            .find(|((property, _), _)| *property == source_name)
+            .map(|((_, values), &index)| values[index].as_ref());
+        let default = rule.and_then(ValueRule::default);
+
+        let value = match (rename.and_then(|rename| rename.fixed_value(name)), source_value) {
+            (Some(value), _) => value,
+            (None, Some(value)) => match rule.and_then(|rule| rule.translate(value)) {
+                Some(translated) => translated,
+                None if target.iter().any(|x| x == value) => value,
+                None => default.ok_or(UnmappedReason::InvalidValue(name))?,
+            },
+            (None, None) => default.ok_or(UnmappedReason::MissingProperty(name))?,
+        };
+        target.iter().position(|x| x == value).ok_or(UnmappedReason::InvalidValue(name))
+    }
+}
+
+/// A block of the target version.
+struct Target<'raw> {
+    base_id: i32,
+    values: PropertyValues<'raw>,
+    /// The value indices of the default blockstate
+    defaults: Vec<usize>,
+}
+
+fn resolve<'raw>(identifier: &Identifier<'raw>, block: &ModernBlockData<'raw>, properties: &PropertyList<'raw>) -> Result<PropertyValues<'raw>, MappingError> {
+    block.property_values(properties).map_err(|property| MappingError::UnknownProperty {
+        block: identifier.to_string(),
+        property: property.to_string(),
+    })
+}
+
+/// Combines the value index of every property into an offset, `index` returns
+/// the value index of a property given its position, name and values.
+fn combine<'raw, F>(target_values: &PropertyValues<'raw>, index: F) -> usize
+where
+    F: Fn(usize, &'raw str, &[Cow<'raw, str>]) -> usize,
+{
+    target_values
+        .iter()
+        .enumerate()
+        .fold(0, |offset, (position, (name, values))| offset * values.len() + index(position, name, values))
+}
+
+/// Finds the value indices given by a fallback blockstate.
+fn fixed_indices(fallback: &BlockStateString, target: &Target) -> Result<Vec<Option<usize>>, MappingError> {
+    let mut fixed = vec![None; target.values.len()];
+    for (name, value) in fallback.properties() {
+        let invalid = |reason: String| MappingError::InvalidFallback {
+            fallback: fallback.to_string(),
+            reason,
+        };
+        let position = target
+            .values
+            .iter()
+            .position(|(property, _)| property == name)
+            .ok_or_else(|| invalid(format!("unknown property \"{}\"", name)))?;
+        let index = target.values[position]
+            .1
+            .iter()
+            .position(|x| x == value)
+            .ok_or_else(|| invalid(format!("invalid value \"{}\" for \"{}\"", value, name)))?;
+        fixed[position] = Some(index);
+    }
+    Ok(fixed)
+}
+
+/// Iterates over the value indices of every blockstate of a block in network
+/// order.
+///
+/// The first property is the most significant one, i.e. the last property
+/// changes with every next blockstate.
+struct StateIndices<'b, 'raw> {
+    values: &'b PropertyValues<'raw>,
+    current: Option<Vec<usize>>,
+}
+
+impl<'b, 'raw> StateIndices<'b, 'raw> {
+    fn new(values: &'b PropertyValues<'raw>) -> Self {
+        let current = if values.iter().any(|(_, values)| values.is_empty()) {
+            None
+        } else {
+            Some(vec![0; values.len()])
+        };
+        Self { values, current }
+    }
+}
+
+impl<'b, 'raw> Iterator for StateIndices<'b, 'raw> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current.take()?;
+        let mut next = current.clone();
+        for (index, (_, values)) in next.iter_mut().zip(self.values).rev() {
+            *index += 1;
+            if *index < values.len() {
+                self.current = Some(next);
+                break;
+            }
+            *index = 0;
+        }
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_lengths<'raw>(lengths: &[usize]) -> PropertyValues<'raw> {
+        lengths
+            .iter()
+            .map(|&len| ("", (0..len).map(|i| Cow::Owned(i.to_string())).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_state_indices() {
+        let values = with_lengths(&[2, 3]);
+        let indices: Vec<Vec<usize>> = StateIndices::new(&values).collect();
+        assert_eq!(indices, vec![vec![0, 0], vec![0, 1], vec![0, 2], vec![1, 0], vec![1, 1], vec![1, 2]]);
+
+        let values = with_lengths(&[]);
+        assert_eq!(StateIndices::new(&values).count(), 1);
+    }
+
+    #[test]
+    fn test_mapping() {
+        let from: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "facing": ["north", "south"] },
+                "blocks": {
+                    "minecraft:air": { "base": 0 },
+                    "minecraft:stairs": { "properties": { "facing": "facing", "waterlogged": "bool" }, "base": 1 },
+                    "minecraft:removed": { "base": 5 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let to: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "facing": ["north", "east", "south"] },
+                "blocks": {
+                    "minecraft:air": { "base": 0 },
+                    "minecraft:new": { "base": 1 },
+                    "minecraft:stairs": { "properties": { "facing": "facing" }, "base": 2 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mapping = StateMapper::new(&from, &to, None).map().unwrap();
+        let states: Vec<(i32, i32)> = mapping.states.into_iter().collect();
+        assert_eq!(states, vec![(0, 0), (1, 2), (2, 2), (3, 4), (4, 4)]);
+        assert_eq!(mapping.unmapped.len(), 1);
+        assert_eq!(mapping.unmapped[0].id, 5);
+        assert!(matches!(mapping.unmapped[0].reason, UnmappedReason::MissingBlock));
+    }
+
+    #[test]
+    fn test_mapping_rules() {
+        let from: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "connection": ["up", "side", "none"] },
+                "blocks": {
+                    "minecraft:wire": { "properties": { "east": "connection" }, "base": 0 },
+                    "minecraft:wall": { "properties": { "east": "bool" }, "base": 3 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let to: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "connection": ["side", "none"], "height": ["none", "low", "tall"] },
+                "blocks": {
+                    "minecraft:wire": { "properties": { "east": "connection", "power": [0, 1] }, "base": 0 },
+                    "minecraft:wall": { "properties": { "east": "height" }, "base": 4 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let rules: MappingRules = serde_json::from_str(
+            r#"{
+                "values": [
+                    { "blocks": ["wire"], "property": "east", "map": { "up": ["side", "none"] } },
+                    { "blocks": ["wire"], "target": "power", "default": "0" },
+                    { "blocks": ["*_wall", "wall"], "property": "east", "map": { "true": "low", "false": "none" } }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let mapping = StateMapper::new(&from, &to, None).map().unwrap();
+        assert!(mapping.states.is_empty());
+
+        let mapping = StateMapper::new(&from, &to, Some(&rules)).map().unwrap();
+        let states: Vec<(i32, i32)> = mapping.states.into_iter().collect();
+        assert_eq!(states, vec![(0, 0), (1, 0), (2, 2), (3, 5), (4, 4)]);
+        assert!(mapping.unmapped.is_empty());
+
+        let invalid: MappingRules =
+            serde_json::from_str(r#"{ "values": [{ "blocks": ["wire"], "property": "east", "map": { "up": ["up", "side"] } }] }"#).unwrap();
+        let error = StateMapper::new(&from, &to, Some(&invalid)).map().unwrap_err();
+        assert!(matches!(error, MappingError::InvalidValueList { property, value, .. } if property == "east" && value == "up"));
+    }
+
+    #[test]
+    fn test_block_renames() {
+        let from: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "variant": ["stone", "oak"] },
+                "blocks": {
+                    "minecraft:grass_path": { "base": 0 },
+                    "minecraft:stone_slab": { "properties": { "variant": "variant" }, "base": 1 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let to: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "kind": ["top", "bottom"] },
+                "blocks": {
+                    "minecraft:dirt_path": { "base": 0 },
+                    "minecraft:stone_slab": { "properties": { "kind": "kind" }, "base": 1 },
+                    "minecraft:oak_slab": { "properties": { "kind": "kind" }, "base": 3 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let rules: MappingRules = serde_json::from_str(
+            r#"{
+                "blocks": [
+                    { "from": "grass_path", "to": "dirt_path" },
+                    { "from": "stone_slab", "when": { "variant": "oak" }, "to": "oak_slab", "set": { "kind": "bottom" } }
+                ],
+                "values": [{ "blocks": ["stone_slab"], "target": "kind", "default": "bottom" }]
+            }"#,
+        )
+        .unwrap();
+
+        let mapping = StateMapper::new(&from, &to, Some(&rules)).map().unwrap();
+        let states: Vec<(i32, i32)> = mapping.states.into_iter().collect();
+        assert_eq!(states, vec![(0, 0), (1, 2), (2, 4)]);
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let from: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "facing": ["north", "south", "up"], "lit": ["true", "false"] },
+                "blocks": {
+                    "minecraft:copper_block": { "base": 0 },
+                    "minecraft:furnace": { "properties": { "facing": "facing", "lit": "lit" }, "base": 1 },
+                    "minecraft:amethyst": { "base": 7 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let to: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "facing": ["north", "south"], "lit": ["true", "false"] },
+                "blocks": {
+                    "minecraft:stone": { "base": 0 },
+                    "minecraft:iron_block": { "base": 1 },
+                    "minecraft:furnace": { "properties": { "facing": "facing", "lit": "lit" }, "base": 2, "default": 5 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let rules: MappingRules = serde_json::from_str(
+            r#"{
+                "fallback": {
+                    "blocks": { "copper_block": "iron_block" },
+                    "partial": true,
+                    "default": "stone"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mapping = StateMapper::new(&from, &to, Some(&rules)).map().unwrap();
+        assert!(mapping.unmapped.is_empty());
+        let fallbacks: Vec<(i32, FallbackStrategy, i32)> = mapping
+            .fallbacks
+            .iter()
+            .map(|state| (state.id, state.strategy, state.target))
+            .collect();
+        assert_eq!(fallbacks, vec![
+            (0, FallbackStrategy::Explicit, 1),
+            (5, FallbackStrategy::Partial, 4),
+            (6, FallbackStrategy::Partial, 5),
+            (7, FallbackStrategy::CatchAll, 0),
+        ]);
+        assert_eq!(mapping.states.len(), 8);
+
+        let invalid: MappingRules = serde_json::from_str(r#"{ "fallback": { "default": "stone[lit=true]" } }"#).unwrap();
+        assert!(matches!(StateMapper::new(&from, &to, Some(&invalid)).map(), Err(MappingError::InvalidFallback { .. })));
+    }
+}
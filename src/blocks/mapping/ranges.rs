@@ -0,0 +1,150 @@
+use std::cmp::Ordering;
+
+use serde::Serialize;
+
+use super::StateMapping;
+
+/// A run of consecutive source ids mapping to consecutive target ids.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct IdRange {
+    /// The first source id of the range
+    pub start: i32,
+    /// The amount of ids in the range
+    pub length: i32,
+    /// What to add to a source id to get its target id
+    pub offset: i32,
+}
+
+impl IdRange {
+    fn compare(&self, id: i32) -> Ordering {
+        if id < self.start {
+            Ordering::Greater
+        } else if id >= self.start + self.length {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
+/// A [`StateMapping`] expressed as ranges with a constant offset.
+///
+/// Most versions insert new blocks somewhere in the middle, which shifts the
+/// ids of every block after them. This makes the mapping a handful of ranges
+/// in which every id is shifted by the same offset. Ids that don't fit in a
+/// range of at least two ids are listed as exceptions. Both lists are sorted
+/// by source id, so ids can be translated using a binary search.
+#[derive(Debug, Default, Serialize)]
+pub struct RangeTable {
+    pub ranges: Vec<IdRange>,
+    pub exceptions: Vec<(i32, i32)>,
+}
+
+impl RangeTable {
+    /// Compresses the mapped states of a mapping, unmapped states aren't part
+    /// of any range or exception.
+    pub fn new(mapping: &StateMapping) -> Self {
+        let mut states: Vec<(i32, i32)> = mapping.states.iter().map(|(&from, &to)| (from, to)).collect();
+        states.sort_unstable();
+
+        let mut table = Self::default();
+        let mut current: Option<IdRange> = None;
+        for (from, to) in states {
+            match &mut current {
+                Some(range) if range.start + range.length == from && range.offset == to - from => range.length += 1,
+                _ => {
+                    if let Some(range) = current.take() {
+                        table.push(range);
+                    }
+                    current = Some(IdRange {
+                        start: from,
+                        length: 1,
+                        offset: to - from,
+                    });
+                },
+            }
+        }
+        if let Some(range) = current {
+            table.push(range);
+        }
+        table
+    }
+
+    fn push(&mut self, range: IdRange) {
+        if range.length > 1 {
+            self.ranges.push(range);
+        } else {
+            self.exceptions.push((range.start, range.start + range.offset));
+        }
+    }
+
+    /// Translates a source id, returns `None` if it isn't mapped.
+    pub fn get(&self, id: i32) -> Option<i32> {
+        if let Ok(index) = self.ranges.binary_search_by(|range| range.compare(id)) {
+            return Some(id + self.ranges[index].offset);
+        }
+        self.exceptions
+            .binary_search_by_key(&id, |&(from, _)| from)
+            .ok()
+            .map(|index| self.exceptions[index].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdRange, RangeTable};
+    use crate::blocks::intermediary::data::ModernBlockList;
+    use crate::blocks::mapping::StateMapper;
+
+    #[test]
+    fn test_ranges() {
+        let from: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "facing": ["north", "south"] },
+                "blocks": {
+                    "minecraft:stone": { "base": 0 },
+                    "minecraft:observer": { "properties": { "facing": "facing", "powered": "bool" }, "base": 1 },
+                    "minecraft:dirt": { "base": 5 },
+                    "minecraft:furnace": { "properties": { "facing": "facing" }, "base": 6 },
+                    "minecraft:grass_path": { "base": 8 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let to: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "facing": ["north", "south"] },
+                "blocks": {
+                    "minecraft:stone": { "base": 0 },
+                    "minecraft:copper_block": { "base": 1 },
+                    "minecraft:observer": { "properties": { "facing": "facing", "powered": "bool" }, "base": 2 },
+                    "minecraft:furnace": { "properties": { "facing": "facing" }, "base": 6 },
+                    "minecraft:dirt": { "base": 8 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mapping = StateMapper::new(&from, &to, None).map().unwrap();
+        let table = RangeTable::new(&mapping);
+        assert_eq!(table.ranges, vec![
+            IdRange {
+                start: 1,
+                length: 4,
+                offset: 1
+            },
+            IdRange {
+                start: 6,
+                length: 2,
+                offset: 0
+            },
+        ]);
+        assert_eq!(table.exceptions, vec![(0, 0), (5, 8)]);
+
+        for (&from, &to) in &mapping.states {
+            assert_eq!(table.get(from), Some(to));
+        }
+        assert_eq!(table.get(8), None);
+        assert_eq!(table.get(-1), None);
+    }
+}
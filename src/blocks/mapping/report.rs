@@ -0,0 +1,284 @@
+use std::fmt::{Display, Formatter};
+
+use ahash::{AHashMap, AHashSet, RandomState};
+use hashlink::LinkedHashMap;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use super::{resolve, MappingError, StateIndices, StateMapping};
+use crate::blocks::intermediary::data::ModernBlockList;
+use crate::util::identifier::Identifier;
+
+/// How a blockstate of the source version is translated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Lossiness {
+    /// The blockstate has its own counterpart
+    Exact,
+    /// The blockstate shares its counterpart with other blockstates
+    ManyToOne,
+    /// The blockstate was resolved using a fallback
+    Fallback,
+    /// The blockstate has no counterpart at all
+    Unmapped,
+}
+
+/// The number of blockstates per [`Lossiness`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LossinessCounts {
+    pub exact: usize,
+    pub many_to_one: usize,
+    pub fallback: usize,
+    pub unmapped: usize,
+}
+
+impl LossinessCounts {
+    /// The total number of blockstates.
+    pub fn total(&self) -> usize { self.exact + self.many_to_one + self.fallback + self.unmapped }
+
+    /// Whether every blockstate is translated exactly.
+    pub fn is_exact(&self) -> bool { self.exact == self.total() }
+
+    /// The share of `count` in the total number of blockstates, in percent.
+    pub fn percentage(&self, count: usize) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => count as f64 * 100.0 / total as f64,
+        }
+    }
+
+    fn add(&mut self, lossiness: Lossiness) {
+        match lossiness {
+            Lossiness::Exact => self.exact += 1,
+            Lossiness::ManyToOne => self.many_to_one += 1,
+            Lossiness::Fallback => self.fallback += 1,
+            Lossiness::Unmapped => self.unmapped += 1,
+        }
+    }
+
+    fn entries(&self) -> [(&'static str, usize); 4] {
+        [("exact", self.exact), ("many_to_one", self.many_to_one), ("fallback", self.fallback), ("unmapped", self.unmapped)]
+    }
+}
+
+impl Serialize for LossinessCounts {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Entry {
+            count: usize,
+            percentage: f64,
+        }
+
+        let mut map = serializer.serialize_map(Some(5))?;
+        map.serialize_entry("total", &self.total())?;
+        for (name, count) in self.entries() {
+            map.serialize_entry(name, &Entry {
+                count,
+                percentage: self.percentage(count),
+            })?;
+        }
+        map.end()
+    }
+}
+
+impl Display for LossinessCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} states", self.total())?;
+        for (name, count) in self.entries() {
+            if count > 0 {
+                write!(f, ", {} {} ({:.1}%)", count, name.replace('_', "-"), self.percentage(count))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Blockstates of the source version that collapse into the same blockstate of
+/// the target version.
+#[derive(Debug, Serialize)]
+pub struct CollapsedStates {
+    pub target: i32,
+    pub states: Vec<i32>,
+}
+
+/// Describes what information is lost by a [`StateMapping`].
+///
+/// Every blockstate of the source version is classified by its
+/// [`Lossiness`]. The counts are aggregated per block and per property name.
+/// For a property, a blockstate only counts as many-to-one when other
+/// blockstates with another value of this property collapse into the same
+/// blockstate, i.e. when the value of this property is actually lost.
+#[derive(Debug, Serialize)]
+pub struct LossinessReport<'raw> {
+    pub total: LossinessCounts,
+    pub blocks: LinkedHashMap<Identifier<'raw>, LossinessCounts, RandomState>,
+    pub properties: LinkedHashMap<&'raw str, LossinessCounts, RandomState>,
+    pub collapsed: Vec<CollapsedStates>,
+}
+
+impl<'raw> LossinessReport<'raw> {
+    /// Generates the report of a mapping, `from` must be the block data the
+    /// mapping was generated from.
+    pub fn new(from: &ModernBlockList<'raw>, mapping: &StateMapping<'raw>) -> Result<Self, MappingError> {
+        let fallbacks: AHashSet<i32> = mapping.fallbacks.iter().map(|state| state.id).collect();
+        let mut by_target: AHashMap<i32, Vec<i32>> = AHashMap::new();
+        for (&id, &target) in &mapping.states {
+            if !fallbacks.contains(&id) {
+                by_target.entry(target).or_default().push(id);
+            }
+        }
+        let lossiness = |id: i32| match mapping.states.get(&id) {
+            None => Lossiness::Unmapped,
+            Some(_) if fallbacks.contains(&id) => Lossiness::Fallback,
+            Some(target) if by_target[target].len() > 1 => Lossiness::ManyToOne,
+            Some(_) => Lossiness::Exact,
+        };
+
+        let mut total = LossinessCounts::default();
+        let mut blocks = LinkedHashMap::with_hasher(RandomState::default());
+        let mut properties = LinkedHashMap::<_, LossinessCounts, _>::with_hasher(RandomState::default());
+        for (identifier, block) in &from.blocks {
+            let values = resolve(identifier, block, &from.properties)?;
+            let states: Vec<Vec<usize>> = StateIndices::new(&values).collect();
+            let mut counts = LossinessCounts::default();
+
+            for (offset, indices) in states.iter().enumerate() {
+                let id = block.base_id + offset as i32;
+                let state = lossiness(id);
+                total.add(state);
+                counts.add(state);
+
+                for (position, (name, _)) in values.iter().enumerate() {
+                    // The value is only lost if a state differing in this value collapses as well
+                    let property = match state {
+                        Lossiness::ManyToOne => {
+                            let target = mapping.states[&id];
+                            let lost = by_target[&target].iter().any(|&other| {
+                                usize::try_from(other - block.base_id)
+                                    .ok()
+                                    .and_then(|other| states.get(other))
+                                    .is_some_and(|other| other[position] != indices[position])
+                            });
+                            if lost {
+                                Lossiness::ManyToOne
+                            } else {
+                                Lossiness::Exact
+                            }
+                        },
+                        state => state,
+                    };
+                    properties.entry(*name).or_insert_with(LossinessCounts::default).add(property);
+                }
+            }
+            blocks.insert(*identifier, counts);
+        }
+
+        let mut collapsed: Vec<_> = by_target
+            .into_iter()
+            .filter(|(_, states)| states.len() > 1)
+            .map(|(target, mut states)| {
+                states.sort_unstable();
+                CollapsedStates { target, states }
+            })
+            .collect();
+        collapsed.sort_unstable_by_key(|collapsed| collapsed.target);
+
+        Ok(Self {
+            total,
+            blocks,
+            properties,
+            collapsed,
+        })
+    }
+}
+
+impl<'raw> Display for LossinessReport<'raw> {
+    /// Lists the totals, then every block and property that isn't translated
+    /// exactly
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Total: {}", self.total)?;
+        if self.blocks.values().any(|counts| !counts.is_exact()) {
+            writeln!(f, "Blocks:")?;
+            for (identifier, counts) in self.blocks.iter().filter(|(_, counts)| !counts.is_exact()) {
+                writeln!(f, "  {}: {}", identifier, counts)?;
+            }
+        }
+        if self.properties.values().any(|counts| !counts.is_exact()) {
+            writeln!(f, "Properties:")?;
+            for (name, counts) in self.properties.iter().filter(|(_, counts)| !counts.is_exact()) {
+                writeln!(f, "  {}: {}", name, counts)?;
+            }
+        }
+        if !self.collapsed.is_empty() {
+            writeln!(f, "Collapsed states:")?;
+            for collapsed in &self.collapsed {
+                writeln!(f, "  {:?} -> {}", collapsed.states, collapsed.target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LossinessCounts, LossinessReport};
+    use crate::blocks::intermediary::data::ModernBlockList;
+    use crate::blocks::mapping::{MappingRules, StateMapper};
+    use crate::util::identifier::Identifier;
+
+    #[test]
+    fn test_report() {
+        let from: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "facing": ["north", "south"], "lit": ["true", "false"] },
+                "blocks": {
+                    "minecraft:furnace": { "properties": { "facing": "facing", "lit": "lit" }, "base": 0 },
+                    "minecraft:stone": { "base": 4 },
+                    "minecraft:copper_block": { "base": 5 },
+                    "minecraft:amethyst": { "base": 6 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let to: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": { "facing": ["north", "south"] },
+                "blocks": {
+                    "minecraft:furnace": { "properties": { "facing": "facing" }, "base": 0 },
+                    "minecraft:stone": { "base": 2 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let rules: MappingRules = serde_json::from_str(r#"{ "fallback": { "blocks": { "copper_block": "stone" } } }"#).unwrap();
+
+        let mapping = StateMapper::new(&from, &to, Some(&rules)).map().unwrap();
+        let report = LossinessReport::new(&from, &mapping).unwrap();
+
+        let counts = |exact, many_to_one, fallback, unmapped| LossinessCounts {
+            exact,
+            many_to_one,
+            fallback,
+            unmapped,
+        };
+        assert_eq!(report.total, counts(1, 4, 1, 1));
+        assert_eq!(report.blocks[&Identifier::from_location("furnace")], counts(0, 4, 0, 0));
+        assert_eq!(report.properties["facing"], counts(4, 0, 0, 0));
+        assert_eq!(report.properties["lit"], counts(0, 4, 0, 0));
+        assert_eq!(
+            report
+                .collapsed
+                .iter()
+                .map(|collapsed| (collapsed.target, collapsed.states.clone()))
+                .collect::<Vec<_>>(),
+            vec![(0, vec![0, 1]), (1, vec![2, 3]),]
+        );
+        assert_eq!(report.total.percentage(report.total.many_to_one), 400.0 / 7.0);
+
+        let json = serde_json::to_value(&report.total).unwrap();
+        assert_eq!(json["total"], 7);
+        assert_eq!(json["exact"]["count"], 1);
+    }
+}
@@ -0,0 +1,262 @@
+use ahash::RandomState;
+use hashlink::LinkedHashMap;
+use serde::Deserialize;
+
+use crate::blocks::intermediary::rules::BlockPattern;
+use crate::util::blockstate::BlockStateString;
+use crate::util::identifier::Identifier;
+
+/// Rules for mapping blockstates between two versions, for everything that
+/// changed in between.
+///
+/// ```json
+/// {
+///     "blocks": [
+///         { "from": "grass_path", "to": "dirt_path" },
+///         { "from": "stone_slab", "when": { "variant": "oak" }, "to": "oak_slab" }
+///     ],
+///     "values": [
+///         { "blocks": ["*_wall"], "property": "east", "map": { "true": "low", "false": "none" } },
+///         { "property": "shape", "map": { "straight": "straight", "inner": ["inner_left", "inner_right"] }, "default": "straight" },
+///         { "blocks": ["redstone_wire"], "target": "power", "default": "0" }
+///     ],
+///     "fallback": {
+///         "blocks": { "copper_block": "iron_block" },
+///         "partial": true,
+///         "default": "minecraft:stone"
+///     }
+/// }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct MappingRules<'raw> {
+    /// Block renames, see [`BlockRename`]
+    #[serde(borrow, default)]
+    pub blocks: Vec<BlockRename<'raw>>,
+    /// Value translations, see [`ValueRule`]
+    #[serde(borrow, default)]
+    pub values: Vec<ValueRule<'raw>>,
+    /// Fallbacks for blockstates without a counterpart, see [`FallbackRules`]
+    #[serde(borrow, default)]
+    pub fallback: FallbackRules<'raw>,
+}
+
+/// Fallbacks for blockstates of the source version without an exact
+/// counterpart in the target version, tried in this order:
+///
+/// 1. `blocks` maps a block of the source version to a blockstate of the target
+///    version. Properties missing in that blockstate are taken from the source
+///    blockstate if possible, otherwise the default state is used
+/// 2. `partial` keeps as many properties of the source blockstate as possible
+///    if the block exists in the target version, using the default state for
+///    the rest
+/// 3. `default` is used for everything else
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FallbackRules<'raw> {
+    #[serde(borrow, default)]
+    pub blocks: LinkedHashMap<Identifier<'raw>, BlockStateString<'raw>, RandomState>,
+    #[serde(default)]
+    pub partial: bool,
+    #[serde(borrow, default)]
+    pub default: Option<BlockStateString<'raw>>,
+}
+
+/// Maps a block of the source version to a block with another identifier in
+/// the target version.
+///
+/// - `from` is the block in the source version
+/// - `when` limits the rename to blockstates with these property values, so one
+///   block can be split into several blocks
+/// - `to` is the block in the target version
+/// - `set` gives fixed values to properties of the target block, so several
+///   blocks can be merged into one block
+///
+/// When multiple renames match a blockstate, the one with the most `when`
+/// constraints wins. Otherwise the first rename wins.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockRename<'raw> {
+    #[serde(borrow)]
+    from: Identifier<'raw>,
+    #[serde(borrow, default)]
+    when: LinkedHashMap<&'raw str, &'raw str, RandomState>,
+    #[serde(borrow)]
+    to: Identifier<'raw>,
+    #[serde(borrow, default)]
+    set: LinkedHashMap<&'raw str, &'raw str, RandomState>,
+}
+
+impl<'raw> BlockRename<'raw> {
+    /// The block in the target version.
+    pub fn to(&self) -> &Identifier<'raw> { &self.to }
+
+    /// The fixed value of a property of the target block, if any.
+    pub fn fixed_value(&self, property: &str) -> Option<&'raw str> { self.set.get(property).copied() }
+}
+
+/// Translates the values of a property between versions.
+///
+/// - `blocks` limits the rule to the given blocks or patterns of the source
+///   version, see [`BlockPattern`]
+/// - `property` is the name of the property in the source version
+/// - `target` is the name of the property in the target version, defaults to
+///   `property`
+/// - `map` translates source values to target values. Multiple source values
+///   can map to the same target value. A source value can also map to a list of
+///   target values, in which case the first one is used. That value has to be
+///   accepted by the target property, a list is never searched for another
+///   value
+/// - `default` is used for values that aren't in `map` and don't exist in the
+///   target version, or when the source block doesn't have the property at all
+///
+/// Values that aren't in `map` are kept as they are. A rule without `property`
+/// only provides a `default` for a target property that the source block
+/// doesn't have.
+///
+/// When multiple rules match, rules for exact block identifiers come before
+/// rules with a pattern, which come before rules for every block. Otherwise
+/// the first rule wins.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValueRule<'raw> {
+    #[serde(borrow, default)]
+    blocks: Vec<BlockPattern<'raw>>,
+    #[serde(default)]
+    property: Option<&'raw str>,
+    #[serde(default)]
+    target: Option<&'raw str>,
+    #[serde(borrow, default)]
+    map: LinkedHashMap<&'raw str, TargetValues<'raw>, RandomState>,
+    #[serde(default)]
+    default: Option<&'raw str>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TargetValues<'raw> {
+    One(&'raw str),
+    #[serde(borrow)]
+    Many(Vec<&'raw str>),
+}
+
+impl<'raw> ValueRule<'raw> {
+    /// The name of the property in the source version.
+    pub fn property(&self) -> Option<&'raw str> { self.property }
+
+    /// The name of the property in the target version.
+    pub fn target(&self) -> Option<&'raw str> { self.target.or(self.property) }
+
+    /// The value used for unknown or missing values.
+    pub fn default(&self) -> Option<&'raw str> { self.default }
+
+    /// The source values mapping to a list of target values, with the first
+    /// value of that list, which is the one [`translate`](Self::translate)
+    /// uses.
+    pub fn value_lists(&self) -> impl Iterator<Item = (&'raw str, Option<&'raw str>)> + '_ {
+        self.map.iter().filter_map(|(&value, target)| match target {
+            TargetValues::One(_) => None,
+            TargetValues::Many(values) => Some((value, values.first().copied())),
+        })
+    }
+
+    /// Translates a source value, returns `None` if the value isn't listed.
+    pub fn translate(&self, value: &str) -> Option<&'raw str> {
+        match self.map.get(value)? {
+            TargetValues::One(value) => Some(value),
+            TargetValues::Many(values) => values.first().copied(),
+        }
+    }
+
+    fn matches(&self, block: &Identifier) -> Option<u8> {
+        if self.blocks.is_empty() {
+            Some(0)
+        } else {
+            self.blocks.iter().filter_map(|pattern| pattern.matches(block)).max()
+        }
+    }
+}
+
+impl<'raw> MappingRules<'raw> {
+    /// Finds the rename for a blockstate of the source version, `value`
+    /// returns the value of a property of that blockstate.
+    pub fn block_rename<'b, F>(&self, block: &Identifier, value: F) -> Option<&BlockRename<'raw>>
+    where
+        F: Fn(&str) -> Option<&'b str>,
+    {
+        let mut best: Option<&BlockRename<'raw>> = None;
+        for rename in self.blocks.iter().filter(|rename| &rename.from == block) {
+            if rename.when.iter().all(|(property, expected)| value(property) == Some(*expected)) && best.is_none_or(|best| rename.when.len() > best.when.len())
+            {
+                best = Some(rename);
+            }
+        }
+        best
+    }
+
+    /// Finds the value rule for a property of the target block.
+    pub fn value_rule(&self, block: &Identifier, target: &str) -> Option<&ValueRule<'raw>> {
+        let mut best: Option<(&ValueRule<'raw>, u8)> = None;
+        for rule in self.values.iter().filter(|rule| rule.target() == Some(target)) {
+            if let Some(specificity) = rule.matches(block) {
+                if best.is_none_or(|(_, best)| specificity > best) {
+                    best = Some((rule, specificity));
+                }
+            }
+        }
+        best.map(|(rule, _)| rule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MappingRules;
+    use crate::util::identifier::Identifier;
+
+    #[test]
+    fn test_value_rules() {
+        let rules: MappingRules = serde_json::from_str(
+            r#"{
+                "values": [
+                    { "property": "east", "map": { "side": "side", "none": "none", "up": ["side", "up"] } },
+                    { "blocks": ["*_wall"], "property": "east", "map": { "true": "low", "false": "none" } },
+                    { "blocks": ["minecraft:stone_wall"], "property": "east", "map": { "true": "tall" } },
+                    { "target": "up", "default": "true" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let wire = Identifier::from_location("redstone_wire");
+        let wall = Identifier::from_location("cobblestone_wall");
+        let stone_wall = Identifier::from_location("stone_wall");
+        assert_eq!(rules.value_rule(&wire, "east").and_then(|rule| rule.translate("up")), Some("side"));
+        assert_eq!(rules.value_rule(&wall, "east").and_then(|rule| rule.translate("true")), Some("low"));
+        assert_eq!(rules.value_rule(&stone_wall, "east").and_then(|rule| rule.translate("true")), Some("tall"));
+        assert_eq!(rules.value_rule(&wall, "up").and_then(|rule| rule.default()), Some("true"));
+        assert!(rules.value_rule(&wall, "west").is_none());
+        assert!(serde_json::from_str::<MappingRules>(r#"{ "values": [{ "property": "a", "unknown": 1 }] }"#).is_err());
+    }
+
+    #[test]
+    fn test_block_renames() {
+        let rules: MappingRules = serde_json::from_str(
+            r#"{
+                "blocks": [
+                    { "from": "stone_slab", "to": "stone_slab" },
+                    { "from": "stone_slab", "when": { "variant": "oak" }, "to": "oak_slab", "set": { "kind": "bottom" } },
+                    { "from": "grass_path", "to": "minecraft:dirt_path" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let slab = Identifier::from_location("stone_slab");
+        let variant = |variant: &'static str| move |property: &str| (property == "variant").then_some(variant);
+        let oak = rules.block_rename(&slab, variant("oak")).unwrap();
+        assert_eq!(oak.to(), &Identifier::from_location("oak_slab"));
+        assert_eq!(oak.fixed_value("kind"), Some("bottom"));
+        assert_eq!(rules.block_rename(&slab, variant("stone")).unwrap().to(), &slab);
+        assert_eq!(rules.block_rename(&Identifier::from_location("grass_path"), |_| None).unwrap().to(), &Identifier::from_location("dirt_path"));
+        assert!(rules.block_rename(&Identifier::from_location("stone"), |_| None).is_none());
+    }
+}
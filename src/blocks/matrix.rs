@@ -0,0 +1,349 @@
+//! Compatibility between many versions of block data at once.
+use std::borrow::Cow;
+use std::io::Write;
+
+use ahash::RandomState;
+use hashlink::LinkedHashMap;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::blocks::intermediary::data::{ModernBlockList, PropertyValues};
+use crate::blocks::intermediary::MetaData;
+use crate::blocks::mapping::{MappingError, RangeTable, StateMapper};
+use crate::util::identifier::Identifier;
+
+#[derive(Debug, Error)]
+pub enum MatrixError {
+    #[error("Block data at position {0} has no metadata to order it by")]
+    MissingMetaData(usize),
+    #[error("Version {0} is present more than once")]
+    DuplicateVersion(i32),
+    #[error("Unknown property \"{property}\" for block \"{block}\" in version {version}")]
+    UnknownProperty {
+        version: i32,
+        block: String,
+        property: String,
+    },
+    #[error(transparent)]
+    Mapping(#[from] MappingError),
+}
+
+/// In which versions something exists, given by their [`MetaData::id`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Presence {
+    pub versions: Vec<i32>,
+    /// The first version it exists in, unless it exists in the oldest version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub introduced: Option<i32>,
+    /// The version after the last version it exists in, unless it exists in
+    /// the newest version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<i32>,
+    #[serde(skip)]
+    complete: bool,
+}
+
+impl Presence {
+    fn new(present: &[bool], versions: &[i32]) -> Self {
+        let first = present.iter().position(|&present| present);
+        let last = present.iter().rposition(|&present| present);
+        Self {
+            versions: versions
+                .iter()
+                .zip(present)
+                .filter(|(_, &present)| present)
+                .map(|(&version, _)| version)
+                .collect(),
+            introduced: first.filter(|&first| first > 0).map(|first| versions[first]),
+            removed: last.and_then(|last| versions.get(last + 1).copied()),
+            complete: present.iter().all(|&present| present),
+        }
+    }
+
+    /// Whether it exists in every version.
+    pub fn is_complete(&self) -> bool { self.complete }
+}
+
+/// The history of a property of a block.
+#[derive(Debug, Serialize)]
+pub struct PropertyHistory<'raw> {
+    #[serde(flatten)]
+    pub presence: Presence,
+    pub values: LinkedHashMap<Cow<'raw, str>, Presence, RandomState>,
+}
+
+/// The history of a block.
+#[derive(Debug, Serialize)]
+pub struct BlockHistory<'raw> {
+    #[serde(flatten)]
+    pub presence: Presence,
+    /// The versions in which the properties, their values or their order
+    /// differ from the previous version containing the block
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub layout_changes: Vec<i32>,
+    #[serde(skip_serializing_if = "LinkedHashMap::is_empty")]
+    pub properties: LinkedHashMap<&'raw str, PropertyHistory<'raw>, RandomState>,
+}
+
+/// How the blockstate ids shift between two consecutive versions, see
+/// [`RangeTable`].
+#[derive(Debug, Serialize)]
+pub struct IdShift {
+    pub from: i32,
+    pub to: i32,
+    pub ranges: usize,
+    pub exceptions: usize,
+    pub unmapped: usize,
+}
+
+/// Which blocks, properties and values exist in which versions.
+///
+/// The versions are ordered by their [`MetaData::id`], blocks, properties and
+/// values are listed in the order they first appear in. For every two
+/// consecutive versions, the amount of id ranges needed to map between them is
+/// listed in `shifts`.
+#[derive(Debug, Serialize)]
+pub struct CompatibilityMatrix<'raw> {
+    pub versions: Vec<MetaData<'raw>>,
+    pub blocks: LinkedHashMap<Identifier<'raw>, BlockHistory<'raw>, RandomState>,
+    pub shifts: Vec<IdShift>,
+}
+
+impl<'raw> CompatibilityMatrix<'raw> {
+    /// Builds the matrix of the given versions of block data, every version
+    /// needs metadata.
+    pub fn new(lists: &[ModernBlockList<'raw>]) -> Result<Self, MatrixError> {
+        let mut lists: Vec<(&MetaData<'raw>, &ModernBlockList<'raw>)> = lists
+            .iter()
+            .enumerate()
+            .map(|(index, list)| {
+                list.metadata
+                    .as_ref()
+                    .map(|metadata| (metadata, list))
+                    .ok_or(MatrixError::MissingMetaData(index))
+            })
+            .collect::<Result<_, _>>()?;
+        lists.sort_by_key(|(metadata, _)| metadata.id);
+        if let Some(window) = lists.windows(2).find(|window| window[0].0.id == window[1].0.id) {
+            return Err(MatrixError::DuplicateVersion(window[0].0.id));
+        }
+        let versions: Vec<i32> = lists.iter().map(|(metadata, _)| metadata.id).collect();
+
+        // The layout of every block in every version, `None` if it doesn't exist
+        let mut layouts = LinkedHashMap::<Identifier<'raw>, Vec<Option<PropertyValues<'raw>>>, RandomState>::default();
+        for (index, (metadata, list)) in lists.iter().enumerate() {
+            for (identifier, block) in &list.blocks {
+                let values = block
+                    .property_values(&list.properties)
+                    .map_err(|property| MatrixError::UnknownProperty {
+                        version: metadata.id,
+                        block: identifier.to_string(),
+                        property: property.to_string(),
+                    })?;
+                layouts.entry(*identifier).or_insert_with(|| vec![None; versions.len()])[index] = Some(values);
+            }
+        }
+
+        let blocks = layouts
+            .into_iter()
+            .map(|(identifier, layouts)| (identifier, BlockHistory::new(&layouts, &versions)))
+            .collect();
+
+        let mut shifts = Vec::new();
+        for window in lists.windows(2) {
+            let mapping = StateMapper::new(window[0].1, window[1].1, None).map()?;
+            let table = RangeTable::new(&mapping);
+            shifts.push(IdShift {
+                from: window[0].0.id,
+                to: window[1].0.id,
+                ranges: table.ranges.len(),
+                exceptions: table.exceptions.len(),
+                unmapped: mapping.unmapped.len(),
+            });
+        }
+
+        Ok(Self {
+            versions: lists.into_iter().map(|(metadata, _)| metadata.clone()).collect(),
+            blocks,
+            shifts,
+        })
+    }
+
+    /// Whether the block is the same in every version.
+    fn is_stable(history: &BlockHistory) -> bool { history.presence.is_complete() && history.layout_changes.is_empty() }
+
+    /// Writes a table with a column for every version and a row for every
+    /// block that isn't the same in every version.
+    ///
+    /// `+` marks a version containing the block, `*` a version in which its
+    /// layout changed.
+    pub fn display(&self) { self.write_table(&mut std::io::stdout().lock()).expect("Could not write to stdout") }
+
+    /// Writes the table of [`display`](Self::display) to `out`.
+    pub fn write_table<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        let ids: Vec<String> = self.versions.iter().map(|metadata| metadata.id.to_string()).collect();
+        let changed: Vec<_> = self.blocks.iter().filter(|(_, history)| !Self::is_stable(history)).collect();
+        let width = changed.iter().map(|(identifier, _)| identifier.to_string().len()).max().unwrap_or(0);
+
+        writeln!(out, "{:width$} {}", "", ids.join(" "), width = width)?;
+        for (identifier, history) in &changed {
+            let cells: Vec<String> = self
+                .versions
+                .iter()
+                .zip(&ids)
+                .map(|(metadata, id)| {
+                    let cell = if history.layout_changes.contains(&metadata.id) {
+                        "*"
+                    } else if history.presence.versions.contains(&metadata.id) {
+                        "+"
+                    } else {
+                        ""
+                    };
+                    format!("{:^width$}", cell, width = id.len())
+                })
+                .collect();
+            writeln!(out, "{:width$} {}", identifier.to_string(), cells.join(" "), width = width)?;
+        }
+        writeln!(out, "{} of {} blocks differ between versions", changed.len(), self.blocks.len())?;
+        for shift in &self.shifts {
+            writeln!(out, "{} -> {}: {} ranges, {} exceptions, {} unmapped blockstates", shift.from, shift.to, shift.ranges, shift.exceptions, shift.unmapped)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'raw> BlockHistory<'raw> {
+    fn new(layouts: &[Option<PropertyValues<'raw>>], versions: &[i32]) -> Self {
+        let present: Vec<bool> = layouts.iter().map(Option::is_some).collect();
+
+        let mut layout_changes = Vec::new();
+        let mut previous: Option<&PropertyValues> = None;
+        for (layout, &version) in layouts.iter().zip(versions) {
+            if let Some(layout) = layout {
+                if previous.is_some_and(|previous| previous != layout) {
+                    layout_changes.push(version);
+                }
+                previous = Some(layout);
+            }
+        }
+
+        let mut properties = LinkedHashMap::<&'raw str, Vec<&[Cow<'raw, str>]>, RandomState>::default();
+        for (index, layout) in layouts.iter().enumerate() {
+            for (name, values) in layout.iter().flatten() {
+                properties.entry(*name).or_insert_with(|| vec![&[]; layouts.len()])[index] = values;
+            }
+        }
+        let properties = properties
+            .into_iter()
+            .map(|(name, values_per_version)| {
+                let present: Vec<bool> = layouts
+                    .iter()
+                    .map(|layout| layout.iter().flatten().any(|(property, _)| *property == name))
+                    .collect();
+                let mut values = LinkedHashMap::<Cow<'raw, str>, Vec<bool>, RandomState>::default();
+                for (index, version_values) in values_per_version.iter().enumerate() {
+                    for value in version_values.iter() {
+                        values.entry(value.clone()).or_insert_with(|| vec![false; layouts.len()])[index] = true;
+                    }
+                }
+                let history = PropertyHistory {
+                    presence: Presence::new(&present, versions),
+                    values: values
+                        .into_iter()
+                        .map(|(value, present)| (value, Presence::new(&present, versions)))
+                        .collect(),
+                };
+                (name, history)
+            })
+            .collect();
+
+        Self {
+            presence: Presence::new(&present, versions),
+            layout_changes,
+            properties,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompatibilityMatrix, MatrixError};
+    use crate::blocks::intermediary::data::ModernBlockList;
+    use crate::util::identifier::Identifier;
+
+    fn list(data: &'static str) -> ModernBlockList<'static> { serde_json::from_str(data).unwrap() }
+
+    #[test]
+    fn test_matrix() {
+        let lists = [
+            list(
+                r#"{
+                    "metadata": { "id": 3 },
+                    "properties": { "facing": ["north", "south", "up"] },
+                    "blocks": {
+                        "minecraft:stone": { "base": 0 },
+                        "minecraft:observer": { "properties": { "facing": "facing", "powered": "bool" }, "base": 1 },
+                        "minecraft:copper_block": { "base": 7 }
+                    }
+                }"#,
+            ),
+            list(
+                r#"{
+                    "metadata": { "id": 1 },
+                    "properties": { "facing": ["north", "south"] },
+                    "blocks": {
+                        "minecraft:stone": { "base": 0 },
+                        "minecraft:observer": { "properties": { "facing": "facing" }, "base": 1 },
+                        "minecraft:grass_path": { "base": 3 }
+                    }
+                }"#,
+            ),
+            list(
+                r#"{
+                    "metadata": { "id": 2 },
+                    "properties": { "facing": ["north", "south"] },
+                    "blocks": {
+                        "minecraft:stone": { "base": 0 },
+                        "minecraft:observer": { "properties": { "facing": "facing" }, "base": 1 }
+                    }
+                }"#,
+            ),
+        ];
+        let matrix = CompatibilityMatrix::new(&lists).unwrap();
+        assert_eq!(matrix.versions.iter().map(|metadata| metadata.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        let stone = &matrix.blocks[&Identifier::from_location("stone")];
+        assert!(stone.presence.is_complete());
+        assert!(stone.layout_changes.is_empty());
+
+        let path = &matrix.blocks[&Identifier::from_location("grass_path")];
+        assert_eq!((path.presence.introduced, path.presence.removed), (None, Some(2)));
+        let copper = &matrix.blocks[&Identifier::from_location("copper_block")];
+        assert_eq!((copper.presence.introduced, copper.presence.removed), (Some(3), None));
+
+        let observer = &matrix.blocks[&Identifier::from_location("observer")];
+        assert_eq!(observer.layout_changes, vec![3]);
+        assert_eq!(observer.properties["powered"].presence.introduced, Some(3));
+        assert_eq!(observer.properties["facing"].values["up"].versions, vec![3]);
+        assert!(observer.properties["facing"].values["north"].is_complete());
+
+        let shifts: Vec<_> = matrix
+            .shifts
+            .iter()
+            .map(|shift| (shift.from, shift.to, shift.ranges, shift.exceptions, shift.unmapped))
+            .collect();
+        assert_eq!(shifts, vec![(1, 2, 1, 0, 1), (2, 3, 0, 1, 2)]);
+
+        let mut table = Vec::new();
+        matrix.write_table(&mut table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table.contains("minecraft:observer     + + *"), "{}", table);
+        assert!(table.ends_with("2 -> 3: 0 ranges, 1 exceptions, 2 unmapped blockstates\n"));
+    }
+
+    #[test]
+    fn test_missing_metadata() {
+        let lists = [list(r#"{ "properties": {}, "blocks": {} }"#)];
+        assert!(matches!(CompatibilityMatrix::new(&lists), Err(MatrixError::MissingMetaData(0))));
+    }
+}
@@ -1,2 +1,5 @@
 pub mod intermediary;
+pub mod legacy;
+pub mod mapping;
+pub mod matrix;
 pub mod raw;
@@ -21,8 +21,8 @@ impl<'a, 'raw> CompactRuleProvider<'a, 'raw> {
     /// - First it makes sure the property name is not `"type"`, this will get
     ///   transformed into `"kind"`
     /// - Secondly it uses the rules, if present, to replace the property name
-    ///   if there's a match based on the property values
-    pub fn transform<'b, I>(&'b self, properties: I) -> impl Iterator<Item = (&'raw str, PropertyKind<'raw>)> + 'b
+    ///   if there's a rule matching the property of this block
+    pub fn transform<'b, I>(&'b self, block: &'b Identifier<'raw>, properties: I) -> impl Iterator<Item = (&'raw str, PropertyKind<'raw>)> + 'b
     where
         I: IntoIterator<Item = (&'raw str, PropertyKind<'raw>)> + 'b,
     {
@@ -33,7 +33,7 @@ impl<'a, 'raw> CompactRuleProvider<'a, 'raw> {
                 name
             };
             if let Some(rules) = self.rules {
-                rules.transform(name, property)
+                rules.transform(block, name, property)
             } else {
                 (name, property)
             }
@@ -125,10 +125,13 @@ impl<'a, 'raw, 'de: 'raw> Visitor<'de> for CompactRuleProvider<'a, 'raw> {
             });
 
             // Extend the list of properties with the properties of this block
-            properties.extend(self.transform(block.properties()).filter_map(|(name, property)| match property {
-                PropertyKind::Enum(property) => Some((name, property)),
-                _ => None,
-            }));
+            properties.extend(
+                self.transform(&identifier, block.properties())
+                    .filter_map(|(name, property)| match property {
+                        PropertyKind::Enum(property) => Some((name, property)),
+                        _ => None,
+                    }),
+            );
 
             // Collect the property types to add to the compacted form of this block (see
             // below)
@@ -147,7 +150,7 @@ impl<'a, 'raw, 'de: 'raw> Visitor<'de> for CompactRuleProvider<'a, 'raw> {
                         PropertyKind::Int([start, end]) => PropertyValue::range(start, end),
                         PropertyKind::Enum(_) => {
                             if let Some(rules) = self.rules {
-                                PropertyValue::enum_name(rules.transform(name, kind).0)
+                                PropertyValue::enum_name(rules.transform(&identifier, name, kind).0)
                             } else {
                                 PropertyValue::enum_name(name)
                             }
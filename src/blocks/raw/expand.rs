@@ -0,0 +1,188 @@
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+use crate::blocks::intermediary::data::{value_indices, ModernBlockData, ModernBlockList, PropertyValues};
+use crate::blocks::intermediary::lookup::LookupError;
+use crate::util::identifier::Identifier;
+
+/// Expands the compact format back into the layout of the Minecraft
+/// generators.
+///
+/// Every block gets its full list of property values and every blockstate is
+/// listed with its id, property values and whether it's the default one. The
+/// `"kind"` property name is turned back into `"type"`.
+///
+/// This type doesn't store the expanded data, it is generated while
+/// serializing.
+pub struct BlockListExpander<'a, 'raw> {
+    blocks: Vec<ExpandedBlock<'a, 'raw>>,
+}
+
+struct ExpandedBlock<'a, 'raw> {
+    identifier: Identifier<'raw>,
+    data: &'a ModernBlockData<'raw>,
+    values: PropertyValues<'raw>,
+}
+
+struct ExpandedProperties<'b, 'raw>(&'b PropertyValues<'raw>);
+
+struct ExpandedStates<'b, 'a, 'raw>(&'b ExpandedBlock<'a, 'raw>);
+
+struct ExpandedState<'b, 'raw> {
+    id: i32,
+    default: bool,
+    values: &'b PropertyValues<'raw>,
+    indices: Vec<usize>,
+}
+
+struct ExpandedStateProperties<'b, 'raw>(&'b ExpandedState<'b, 'raw>);
+
+impl<'a, 'raw> BlockListExpander<'a, 'raw> {
+    pub fn new(data: &'a ModernBlockList<'raw>) -> Result<Self, LookupError> {
+        let blocks = data
+            .blocks
+            .iter()
+            .map(|(identifier, block)| {
+                let values = block
+                    .property_values(&data.properties)
+                    .map_err(|property| LookupError::UnknownProperty {
+                        block: identifier.to_string(),
+                        property: property.to_string(),
+                    })?;
+                Ok(ExpandedBlock {
+                    identifier: *identifier,
+                    data: block,
+                    values,
+                })
+            })
+            .collect::<Result<_, LookupError>>()?;
+        Ok(Self { blocks })
+    }
+}
+
+/// Reverts the `"type"` to `"kind"` transformation done while compacting.
+fn property_name(name: &str) -> &str {
+    if name == "kind" {
+        "type"
+    } else {
+        name
+    }
+}
+
+impl<'a, 'raw> Serialize for BlockListExpander<'a, 'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.blocks.len()))?;
+        for block in &self.blocks {
+            map.serialize_entry(&block.identifier, block)?;
+        }
+        map.end()
+    }
+}
+
+impl<'a, 'raw> Serialize for ExpandedBlock<'a, 'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        if !self.values.is_empty() {
+            map.serialize_entry("properties", &ExpandedProperties(&self.values))?;
+        }
+        map.serialize_entry("states", &ExpandedStates(self))?;
+        map.end()
+    }
+}
+
+impl<'b, 'raw> Serialize for ExpandedProperties<'b, 'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, values) in self.0 {
+            map.serialize_entry(property_name(name), values)?;
+        }
+        map.end()
+    }
+}
+
+impl<'b, 'a, 'raw> Serialize for ExpandedStates<'b, 'a, 'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let block = self.0;
+        let state_count = block.values.iter().map(|(_, values)| values.len()).product();
+        let default_id = block.data.default_id();
+
+        let mut seq = serializer.serialize_seq(Some(state_count))?;
+        for offset in 0..state_count {
+            let id = block.data.base_id + offset as i32;
+            seq.serialize_element(&ExpandedState {
+                id,
+                default: id == default_id,
+                values: &block.values,
+                indices: value_indices(&block.values, offset),
+            })?;
+        }
+        seq.end()
+    }
+}
+
+impl<'b, 'raw> Serialize for ExpandedState<'b, 'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        if self.default {
+            map.serialize_entry("default", &true)?;
+        }
+        map.serialize_entry("id", &self.id)?;
+        if !self.values.is_empty() {
+            map.serialize_entry("properties", &ExpandedStateProperties(self))?;
+        }
+        map.end()
+    }
+}
+
+impl<'b, 'raw> Serialize for ExpandedStateProperties<'b, 'raw> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let state = self.0;
+        let mut map = serializer.serialize_map(Some(state.values.len()))?;
+        for ((name, values), &index) in state.values.iter().zip(&state.indices) {
+            map.serialize_entry(property_name(name), &values[index])?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    const RAW: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/blocks.json"));
+    const RULES: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rules.json"));
+
+    #[test]
+    fn test_expand_round_trip() {
+        let rules = crate::load_rules(RULES).unwrap();
+        let compact = crate::compact(RAW, Some(&rules), None).unwrap();
+        let compact_json = serde_json::to_string(&compact).unwrap();
+
+        let expanded = serde_json::to_string(&BlockListExpander::new(&compact).unwrap()).unwrap();
+        let original: Value = serde_json::from_str(RAW).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&expanded).unwrap(), original);
+
+        let recompacted = crate::compact(&expanded, Some(&rules), None).unwrap();
+        assert_eq!(serde_json::to_string(&recompacted).unwrap(), compact_json);
+    }
+}
@@ -5,6 +5,7 @@ use serde::Deserialize;
 use self::property::PropertyKind;
 
 pub mod de;
+pub mod expand;
 // pub mod modern;
 pub mod property;
 
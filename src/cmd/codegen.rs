@@ -0,0 +1,44 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use data_compat_mc::blocks::intermediary::data::ModernBlockList;
+use data_compat_mc::codegen::blocks::BlockStateGenerator;
+
+use super::file::{InputFile, OutputFile};
+
+#[derive(Args, Debug)]
+/// Generates Rust code from intermediary data
+///
+/// Fed with an intermediary data file from mc-data, this command will
+/// generate a Rust module containing an enum for every enum property, a struct
+/// for every block with properties and functions to convert between these
+/// and blockstate ids.
+pub struct CodegenCommand {
+    /// File containing intermediary data from mc-data
+    input: InputFile,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+}
+
+impl CodegenCommand {
+    pub fn generate_code(&self) -> Result<()> {
+        let data: ModernBlockList = self.input.deserialized()?;
+
+        let code = BlockStateGenerator::new(&data).generate()?;
+
+        match &self.output {
+            Some(output) => {
+                if let Some(mut writer) = output.writer()? {
+                    writer.write_all(code.as_bytes())?;
+                    eprintln!("Successfully generated code \u{2705}");
+                } else {
+                    eprintln!("Aborted");
+                }
+            },
+            None => print!("{}", code),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use data_compat_mc::commands::CommandNode;
+use data_compat_mc::registries::RegistryList;
+use data_compat_mc::Identifier;
+
+use super::file::{InputFile, OutputFile};
+use super::ReportFormat;
+
+#[derive(Args, Debug)]
+/// Works with command trees
+pub struct CommandsCommand {
+    #[clap(subcommand)]
+    command: CommandsSubCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CommandsSubCommands {
+    Diff(CommandDiffCommand),
+    Flatten(FlattenCommand),
+}
+
+#[derive(Args, Debug)]
+/// Shows which commands changed between two versions
+///
+/// Fed with the `commands.json` reports of two versions, this command will
+/// list every node of the command tree that was added or removed, and every
+/// argument whose parser changed.
+pub struct CommandDiffCommand {
+    /// File containing the commands report of the older version
+    from: InputFile,
+    /// File containing the commands report of the newer version
+    to: InputFile,
+    /// How to report the changes, `json` prints a machine-readable report to
+    /// stdout
+    #[clap(long, arg_enum, default_value = "text")]
+    report: ReportFormat,
+}
+
+#[derive(Args, Debug)]
+/// Flattens a command tree into the Declare Commands layout
+///
+/// Fed with the `commands.json` report from the Minecraft generators, this
+/// command will generate the node array of the Declare Commands packet, in
+/// which every node refers to its children and redirect by their index.
+pub struct FlattenCommand {
+    /// File containing the commands report from Minecraft
+    input: InputFile,
+    /// File containing compacted registries of the same version, used to look
+    /// up the ids of the argument parsers
+    #[clap(short, long)]
+    registries: Option<InputFile>,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+}
+
+impl CommandsCommand {
+    pub fn execute(&self) -> Result<()> {
+        match &self.command {
+            CommandsSubCommands::Diff(cmd) => cmd.diff(),
+            CommandsSubCommands::Flatten(cmd) => cmd.flatten(),
+        }
+    }
+}
+
+impl CommandDiffCommand {
+    pub fn diff(&self) -> Result<()> {
+        let from: CommandNode = self.from.deserialized()?;
+        let to: CommandNode = self.to.deserialized()?;
+
+        let changes = data_compat_mc::commands::diff(&from, &to);
+        match self.report {
+            ReportFormat::Json => println!("{}", serde_json::to_string(&changes)?),
+            ReportFormat::Text if changes.is_empty() => eprintln!("No commands changed \u{2705}"),
+            ReportFormat::Text => {
+                for change in &changes {
+                    println!("{}", change);
+                }
+                eprintln!("{} nodes changed", changes.len());
+            },
+        }
+
+        Ok(())
+    }
+}
+
+impl FlattenCommand {
+    pub fn flatten(&self) -> Result<()> {
+        let root: CommandNode = self.input.deserialized()?;
+        eprintln!("Loaded {} command nodes successfully \u{2705}", root.count());
+
+        let registries: Option<RegistryList> = self.registries.as_ref().map(|registries| registries.deserialized()).transpose()?;
+        let parsers = registries
+            .as_ref()
+            .map(|registries| {
+                registries
+                    .registries
+                    .get(&Identifier::from_location("command_argument_type"))
+                    .context("The registries don't contain minecraft:command_argument_type")
+            })
+            .transpose()?;
+        let commands = root.flatten(parsers)?;
+
+        if super::write_json(self.output.as_ref(), &commands, !self.no_pretty)? {
+            eprintln!("Successfully flattened {} nodes \u{2705}", commands.nodes.len());
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use data_compat_mc::blocks::intermediary::data::ModernBlockList;
+use data_compat_mc::blocks::mapping::{FallbackStrategy, LossinessReport, MappingArray, MappingRules, RangeTable, StateMapper, StateMapping};
+use data_compat_mc::codegen::mapping::MappingArrayGenerator;
+
+use super::file::{InputFile, OutputFile};
+use super::ReportFormat;
+
+#[derive(Args, Debug)]
+/// Generates a blockstate mapping between two versions
+///
+/// Fed with two intermediary data files from mc-data, this command will map
+/// every blockstate id of the first version to the equivalent blockstate id
+/// of the second version. Blocks are matched by identifier and properties by
+/// name and value, blockstates without an exact counterpart are listed
+/// separately. Renamed blocks and values that changed between the versions
+/// can be specified in a mapping rules file, as well as fallbacks for
+/// blockstates without an exact counterpart. A report of the information lost
+/// by the mapping can be printed as well. Instead of every single blockstate
+/// id, the mapping can also be written as ranges of ids shifted by the same
+/// offset, or as a binary array indexed by blockstate id together with a Rust
+/// module embedding it.
+pub struct CompareCommand {
+    /// File containing intermediary data of the version to map from
+    from: InputFile,
+    /// File containing intermediary data of the version to map to
+    to: InputFile,
+    /// A file specifying how blocks and values changed between the versions
+    #[clap(short, long)]
+    rules: Option<InputFile>,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+    /// Prints a report of which blockstates are translated exactly, collapse
+    /// into the same blockstate or have no counterpart, `json` writes a
+    /// machine-readable report to `--report-output`, or to stdout if the
+    /// mapping is written to `--output`
+    #[clap(long, arg_enum)]
+    report: Option<ReportFormat>,
+    /// The file to write the json report to
+    #[clap(long)]
+    report_output: Option<OutputFile>,
+    /// Writes the mapping as ranges of ids with a constant offset plus
+    /// exceptions instead of a full id mapping
+    #[clap(long)]
+    ranges: bool,
+    /// Also writes the mapping as a binary array indexed by source blockstate
+    /// id, both files need metadata
+    #[clap(long)]
+    binary: Option<OutputFile>,
+    /// Also writes a Rust module embedding the binary array, which is expected
+    /// next to the module
+    #[clap(long, requires = "binary")]
+    rust: Option<OutputFile>,
+}
+
+impl CompareCommand {
+    pub fn compare(&self) -> Result<()> {
+        let from: ModernBlockList = self.from.deserialized()?;
+        let to: ModernBlockList = self.to.deserialized()?;
+
+        let rules: Option<MappingRules> = self.rules.as_ref().map(|rules| rules.deserialized()).transpose()?;
+
+        let mapping = StateMapper::new(&from, &to, rules.as_ref()).map()?;
+
+        eprintln!("Mapped {} blockstates \u{2705}", mapping.states.len());
+        if !mapping.fallbacks.is_empty() {
+            let count = |strategy| mapping.fallbacks.iter().filter(|state| state.strategy == strategy).count();
+            eprintln!(
+                "{} blockstates were resolved using fallbacks ({} explicit, {} partial, {} catch-all) \u{26A0}\u{FE0F}",
+                mapping.fallbacks.len(),
+                count(FallbackStrategy::Explicit),
+                count(FallbackStrategy::Partial),
+                count(FallbackStrategy::CatchAll)
+            );
+        }
+        if !mapping.unmapped.is_empty() {
+            eprintln!("{} blockstates have no exact counterpart \u{26A0}\u{FE0F}", mapping.unmapped.len());
+        }
+
+        let ranges = RangeTable::new(&mapping);
+        eprintln!("The mapping needs {} ranges and {} exceptions", ranges.ranges.len(), ranges.exceptions.len());
+
+        let reported = match self.report {
+            Some(ReportFormat::Text) => {
+                eprint!("{}", LossinessReport::new(&from, &mapping)?);
+                true
+            },
+            Some(ReportFormat::Json) => super::write_report(self.output.as_ref(), self.report_output.as_ref(), &LossinessReport::new(&from, &mapping)?)?,
+            None => true,
+        };
+        if !reported {
+            eprintln!("Aborted");
+            return Ok(());
+        }
+
+        if let Some(binary) = &self.binary {
+            self.write_binary(binary, &mapping)?;
+        }
+
+        let written = if self.ranges {
+            super::write_json(self.output.as_ref(), &ranges, !self.no_pretty)?
+        } else {
+            super::write_json(self.output.as_ref(), &mapping, !self.no_pretty)?
+        };
+        if written {
+            eprintln!("Successfully generated mapping \u{2705}");
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+
+    fn write_binary(&self, binary: &OutputFile, mapping: &StateMapping) -> Result<()> {
+        let array = MappingArray::new(mapping)?;
+        let width = array.width();
+        match binary.writer()? {
+            Some(mut writer) => {
+                writer.write_all(&array.to_bytes(width)?)?;
+                eprintln!("Successfully wrote binary mapping of {} blockstates \u{2705}", array.states.len());
+            },
+            None => eprintln!("Aborted"),
+        }
+
+        if let Some(rust) = &self.rust {
+            let path = binary
+                .name()
+                .file_name()
+                .and_then(|name| name.to_str())
+                .context("The binary file needs a valid file name")?;
+            let code = MappingArrayGenerator::new(&array, width, path).generate()?;
+            match rust.writer()? {
+                Some(mut writer) => {
+                    writer.write_all(code.as_bytes())?;
+                    eprintln!("Successfully generated code \u{2705}");
+                },
+                None => eprintln!("Aborted"),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use clap::Args;
+use data_compat_mc::blocks::raw::expand::BlockListExpander;
+use data_compat_mc::ModernBlockList;
+
+use super::file::{InputFile, OutputFile};
+
+#[derive(Args, Debug)]
+/// Expands intermediary data back into raw data
+///
+/// Fed with an intermediary data file from mc-data, this command will
+/// generate the same layout the Minecraft generators use, listing every
+/// blockstate of every block. This can be used to verify the compaction or
+/// to feed tools that only understand the vanilla format.
+pub struct ExpandCommand {
+    /// File containing intermediary data from mc-data
+    input: InputFile,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+}
+
+impl ExpandCommand {
+    pub fn expand(&self) -> Result<()> {
+        let data: ModernBlockList = self.input.deserialized()?;
+
+        let expanded = BlockListExpander::new(&data)?;
+
+        if super::write_json(self.output.as_ref(), &expanded, !self.no_pretty)? {
+            eprintln!("Successfully expanded data \u{2705}");
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+}
@@ -20,7 +20,7 @@ impl InputFile {
 
     /// Convenience function to automatically deserialize
     pub fn deserialized<'raw, T: Deserialize<'raw>>(&'raw self) -> anyhow::Result<T> {
-        let result = serde_json::from_str::<T>(&self.contents).context("Could not deserialize to the requested format")?;
+        let result = serde_json::from_str::<T>(&self.contents).with_context(|| format!("Could not deserialize {:?} to the requested format", self.name()))?;
         Ok(result)
     }
 
@@ -11,6 +11,9 @@ pub struct OutputFile {
 }
 
 impl OutputFile {
+    /// Returns the name of the file
+    pub fn name(&self) -> &PathBuf { &self.output }
+
     pub fn writer(&self) -> anyhow::Result<Option<impl Write>> {
         if self.output.exists() {
             print!("{:?} already exists, do you want to overwrite it? [y/N] ", self.output);
@@ -25,6 +28,10 @@ impl OutputFile {
     }
 }
 
+impl From<PathBuf> for OutputFile {
+    fn from(output: PathBuf) -> Self { Self { output } }
+}
+
 impl FromStr for OutputFile {
     type Err = Infallible;
 
@@ -1,8 +1,9 @@
 use anyhow::Result;
 use clap::Args;
+use data_compat_mc::blocks::intermediary::data::{ModernBlockData, ModernBlockList, PropertyValue};
 
-use crate::blocks::intermediary::data::{ModernBlockData, ModernBlockList, PropertyValue};
-use crate::util::file::InputFile;
+use super::file::InputFile;
+use super::ExitError;
 
 #[derive(Args, Debug)]
 pub struct InfoCommand {
@@ -96,6 +97,7 @@ impl InfoCommand {
                 });
                 println!();
             }
+            return Err(ExitError::Healthcheck.into());
         }
 
         Ok(())
@@ -1,14 +1,12 @@
-use anyhow::Result;
+use std::io::Write;
+
+use anyhow::{bail, Result};
 use clap::Args;
-use serde::de::DeserializeSeed;
-use serde_json::Deserializer;
+use data_compat_mc::blocks::intermediary::suggest::{NameChooser, SuggestedRules};
+use data_compat_mc::{CollisionList, MetaData, ModernPropertyRules};
 
-use crate::blocks::intermediary::collisions::{CollisionList, CollisionRuleProvider};
-use crate::blocks::intermediary::data::ModernBlockList;
-use crate::blocks::intermediary::rules::ModernPropertyRules;
-use crate::blocks::intermediary::MetaData;
-use crate::blocks::raw::de::CompactRuleProvider;
-use crate::util::file::{InputFile, OutputFile};
+use super::file::{InputFile, OutputFile};
+use super::{ExitError, ReportFormat, Reported};
 
 #[derive(Args, Debug)]
 /// Generates intermediate data
@@ -16,7 +14,8 @@ use crate::util::file::{InputFile, OutputFile};
 /// Fed with a raw data file from the Minecraft generators, this command will
 /// generate a more compact version of the same data, applying tricks to
 /// minimize the size in a lossless manner. To avoid property collisions between
-/// blocks, a rules file can be specified (property collisions fail!).
+/// blocks, a rules file can be specified (property collisions fail!). These
+/// rules can also be chosen interactively using `--interactive`.
 pub struct IntermediaryCommand {
     /// File containing raw generated data from Minecraft
     input: InputFile,
@@ -36,6 +35,20 @@ pub struct IntermediaryCommand {
     #[clap(long)]
     /// Does not pretty-print the resulting json data
     no_pretty: bool,
+    /// How to report property collisions, `json` writes a machine-readable
+    /// report to `--report-output`, or to stdout if the data is written to
+    /// `--output`
+    #[clap(long, arg_enum, default_value = "text")]
+    report: ReportFormat,
+    /// The file to write the json report to
+    #[clap(long)]
+    report_output: Option<OutputFile>,
+    /// Prompts for new property names when there are name collisions
+    #[clap(short, long, requires = "save-rules")]
+    interactive: bool,
+    /// The file to save the interactively chosen rules to
+    #[clap(long)]
+    save_rules: Option<OutputFile>,
 }
 
 impl IntermediaryCommand {
@@ -44,51 +57,112 @@ impl IntermediaryCommand {
         let data = self.input.data();
 
         // Load rules
-        let rules: Option<ModernPropertyRules> = self.rules.as_ref().map(|rules| rules.deserialized()).transpose()?;
+        let chosen_rules;
+        let mut rules: Option<ModernPropertyRules> = self.rules.as_ref().map(|rules| rules.deserialized()).transpose()?;
 
         // Property collisions
-        eprintln!("Checking for property collisions...");
-        let collisions = CollisionRuleProvider::new(rules.as_ref());
-        let collisions: CollisionList = collisions.deserialize(&mut Deserializer::from_str(data))?;
+        let json_report = self.report == ReportFormat::Json;
+        if !json_report {
+            eprintln!("Checking for property collisions...");
+        }
+        let mut collisions = data_compat_mc::check_collisions(data, rules.as_ref())?;
+
+        if json_report {
+            if !super::write_report(self.output.as_ref(), self.report_output.as_ref(), &collisions)? {
+                eprintln!("Aborted");
+                return Ok(());
+            }
+        } else {
+            collisions.display();
+        }
+        if collisions.should_exit() && self.interactive {
+            let chosen = Self::resolve_interactively(data, rules.as_ref(), &collisions)?;
+            chosen_rules = serde_json::to_string(&chosen)?;
+            if !super::write_json(self.save_rules.as_ref(), &chosen, true)? {
+                eprintln!("Aborted");
+                return Ok(());
+            }
+            eprintln!("Saved the chosen rules \u{2705}");
 
-        collisions.display();
+            rules = Some(data_compat_mc::load_rules(&chosen_rules)?);
+            collisions = data_compat_mc::check_collisions(data, rules.as_ref())?;
+        }
         if collisions.should_exit() {
-            eprintln!("Could not continue due to one or more collisions in block properties.\nPlease specify a rules file to resolve these");
-            return Ok(());
+            if json_report {
+                return Err(Reported(ExitError::Collisions).into());
+            }
+            eprintln!("Please specify a rules file to resolve these");
+            return Err(ExitError::Collisions.into());
+        }
+        if !json_report {
+            eprintln!("No serious collisions found, slight inefficiencies will have been signaled by now. \u{2705}");
         }
-        eprintln!("No serious collisions found, slight inefficiencies will have been signaled by now. \u{2705}");
 
         // Compact data and print to output
         let metadata = self
             .id
             .map(|id| MetaData::new(id, self.display_name.as_deref(), self.note.as_deref()));
-        let compacter = CompactRuleProvider::new(rules.as_ref(), metadata);
-        let modern_data: ModernBlockList = compacter.deserialize(&mut Deserializer::from_str(data))?;
-
-        match &self.output {
-            Some(output) => {
-                if let Some(writer) = output.writer()? {
-                    if self.no_pretty {
-                        serde_json::to_writer(writer, &modern_data)?;
-                    } else {
-                        serde_json::to_writer_pretty(writer, &modern_data)?;
+        let modern_data = data_compat_mc::compact(data, rules.as_ref(), metadata)?;
+
+        if super::write_json(self.output.as_ref(), &modern_data, !self.no_pretty)? {
+            eprintln!("Successfully compacted data \u{2705}");
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+
+    /// Prompts for a new name for every set of values involved in a name
+    /// collision. One set of values can keep the original name.
+    ///
+    /// The prompts are written to stderr, as stdout may receive the compacted
+    /// data.
+    fn resolve_interactively<'raw>(
+        data: &'raw str,
+        rules: Option<&ModernPropertyRules<'raw>>,
+        collisions: &CollisionList<'raw>,
+    ) -> Result<SuggestedRules<'raw>> {
+        let usages = data_compat_mc::collect_usages(data, rules, collisions)?;
+        let mut chooser = NameChooser::new(rules, collisions, &usages);
+
+        let mut names: Vec<_> = collisions.by_name().keys().copied().collect();
+        names.sort_unstable();
+        for name in names {
+            let variants = chooser.start(name);
+            eprintln!("========");
+            eprintln!("Property \"{}\" is used with {} different sets of values", name, variants.len());
+
+            for values in variants {
+                if chooser.is_named(values) {
+                    eprintln!("{:?} is named by the rules file, keeping it", values.fields());
+                    continue;
+                }
+                let blocks: Vec<_> = usages.blocks(name, values).iter().map(ToString::to_string).collect();
+                eprintln!("{:?} is used by {}", values.fields(), blocks.join(", "));
+
+                let default = chooser.default_name(name, values);
+                loop {
+                    eprint!("New name for these values [{}]: ", default);
+                    std::io::stderr().flush()?;
+                    let mut answer = String::new();
+                    if std::io::stdin().read_line(&mut answer)? == 0 {
+                        bail!("Unexpected end of input while choosing property names");
+                    }
+                    let answer = match answer.trim() {
+                        "" => default.as_str(),
+                        answer => answer,
+                    };
+
+                    match chooser.choose(name, values, answer) {
+                        Ok(()) => break,
+                        Err(error) => eprintln!("{} \u{274C}", error),
                     }
-                    eprintln!("Successfully compacted data \u{2705}");
-                } else {
-                    eprintln!("Aborted");
                 }
-            },
-            None => {
-                let result = if self.no_pretty {
-                    serde_json::to_string(&modern_data)?
-                } else {
-                    serde_json::to_string_pretty(&modern_data)?
-                };
-                println!("{}", result);
-                eprintln!("========\nSuccessfully compacted data \u{2705}");
-            },
+            }
         }
+        eprintln!("========");
 
-        Ok(())
+        Ok(chooser.finish())
     }
 }
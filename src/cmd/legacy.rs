@@ -0,0 +1,66 @@
+use anyhow::Result;
+use clap::Args;
+use data_compat_mc::blocks::intermediary::data::ModernBlockList;
+use data_compat_mc::blocks::legacy::{LegacyBlockTable, LegacyMapping};
+
+use super::file::{InputFile, OutputFile};
+
+#[derive(Args, Debug)]
+/// Maps pre-1.13 blocks to flattened blockstates
+///
+/// Fed with a legacy block table, mapping `id:meta` pairs to blockstate
+/// strings, and an intermediary data file from mc-data, this command will map
+/// every legacy `id << 4 | meta` value to the blockstate id of the flattened
+/// version. Legacy blocks whose blockstate can't be found are reported
+/// separately.
+pub struct LegacyCommand {
+    /// File containing the legacy block table
+    table: InputFile,
+    /// File containing intermediary data of the flattened version
+    input: InputFile,
+    /// Maps blockstate ids to legacy values instead
+    #[clap(long)]
+    reverse: bool,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+}
+
+impl LegacyCommand {
+    pub fn map_legacy(&self) -> Result<()> {
+        let table: LegacyBlockTable = self.table.deserialized()?;
+        let data: ModernBlockList = self.input.deserialized()?;
+
+        let mapping = LegacyMapping::new(&table, &data)?;
+
+        eprintln!("Mapped {} legacy blocks \u{2705}", mapping.states.len());
+        if !mapping.unmapped.is_empty() {
+            for state in &mapping.unmapped {
+                eprintln!("{} ({}) -> {}: {}", state.legacy, state.value, state.state, state.reason);
+            }
+            eprintln!("{} legacy blocks could not be mapped \u{26A0}\u{FE0F}", mapping.unmapped.len());
+        }
+        if !mapping.missing.is_empty() {
+            for legacy in mapping.missing.chunk_by(|a, b| a.id == b.id) {
+                let metas: Vec<_> = legacy.iter().map(|legacy| legacy.meta.to_string()).collect();
+                eprintln!("{}: no entry for metas {}", legacy[0].id, metas.join(", "));
+            }
+            eprintln!("{} legacy metas of known ids have no entry \u{26A0}\u{FE0F}", mapping.missing.len());
+        }
+
+        let written = if self.reverse {
+            super::write_json(self.output.as_ref(), &mapping.reverse(), !self.no_pretty)?
+        } else {
+            super::write_json(self.output.as_ref(), &mapping, !self.no_pretty)?
+        };
+        if written {
+            eprintln!("Successfully generated mapping \u{2705}");
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+}
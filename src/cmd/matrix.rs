@@ -0,0 +1,60 @@
+use anyhow::Result;
+use clap::Args;
+use data_compat_mc::blocks::intermediary::data::ModernBlockList;
+use data_compat_mc::blocks::matrix::CompatibilityMatrix;
+
+use super::file::{InputFile, OutputFile};
+use super::ReportFormat;
+
+#[derive(Args, Debug)]
+/// Shows which blocks exist in which versions
+///
+/// Fed with any number of intermediary data files from mc-data, this command
+/// will list which blocks, properties and values exist in which versions,
+/// ordered by the version id in their metadata. It also lists the version each
+/// of them was introduced or removed in and the versions in which the state
+/// layout of a block changed.
+pub struct MatrixCommand {
+    /// Files containing intermediary data of every version
+    #[clap(required = true)]
+    inputs: Vec<InputFile>,
+    /// How to show the matrix, `text` prints a table of the blocks that
+    /// differ between versions and `json` prints the full matrix
+    #[clap(long, arg_enum, default_value = "text")]
+    report: ReportFormat,
+    /// The file to write the table or the json matrix to
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+}
+
+impl MatrixCommand {
+    pub fn generate_matrix(&self) -> Result<()> {
+        let lists: Vec<ModernBlockList> = self.inputs.iter().map(|input| input.deserialized()).collect::<Result<_>>()?;
+        let matrix = CompatibilityMatrix::new(&lists)?;
+
+        match self.report {
+            ReportFormat::Text => match &self.output {
+                Some(output) => match output.writer()? {
+                    Some(mut writer) => {
+                        matrix.write_table(&mut writer)?;
+                        eprintln!("Successfully wrote matrix of {} versions \u{2705}", matrix.versions.len());
+                    },
+                    None => eprintln!("Aborted"),
+                },
+                None => matrix.display(),
+            },
+            ReportFormat::Json => {
+                if super::write_json(self.output.as_ref(), &matrix, !self.no_pretty)? {
+                    eprintln!("Successfully generated matrix of {} versions \u{2705}", matrix.versions.len());
+                } else {
+                    eprintln!("Aborted");
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
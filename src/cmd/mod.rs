@@ -1,5 +1,151 @@
+use anyhow::{bail, Result};
+use clap::ArgEnum;
+use data_compat_mc::nbt::NbtError;
+use serde::Serialize;
+use thiserror::Error;
+
+use self::file::OutputFile;
+
+mod codegen;
+mod commands;
+mod compare;
+mod expand;
+mod file;
 mod info;
 mod intermediary;
+mod legacy;
+mod matrix;
+mod packets;
+mod query;
+mod registries;
+mod registry_map;
+mod rules;
+mod schematic;
 
+pub use codegen::CodegenCommand;
+pub use commands::CommandsCommand;
+pub use compare::CompareCommand;
+pub use expand::ExpandCommand;
 pub use info::InfoCommand;
 pub use intermediary::IntermediaryCommand;
+pub use legacy::LegacyCommand;
+pub use matrix::MatrixCommand;
+pub use packets::PacketsCommand;
+pub use query::QueryCommand;
+pub use registries::RegistriesCommand;
+pub use registry_map::RegistryMapCommand;
+pub use rules::RulesCommand;
+pub use schematic::SchematicCommand;
+
+/// Errors that should end the program with their own exit code.
+#[derive(Debug, Error)]
+pub enum ExitError {
+    #[error("Could not continue due to one or more collisions in block properties")]
+    Collisions,
+    #[error("The healthcheck failed")]
+    Healthcheck,
+    #[error("The rules file contains one or more issues")]
+    InvalidRules,
+}
+
+/// An [`ExitError`] that was already reported in a machine-readable form,
+/// the program only exits with its exit code.
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub struct Reported(pub ExitError);
+
+/// Whether the error was already reported, see [`Reported`].
+pub fn is_reported(error: &anyhow::Error) -> bool { error.chain().any(|cause| cause.is::<Reported>()) }
+
+/// The format used to report problems in the input data.
+#[derive(ArgEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+/// Returns the exit code for an error.
+///
+/// - property collisions and issues in a rules file exit with
+///   [`exitcode::CONFIG`], as they need a (fixed) rules file to be resolved
+/// - a failed healthcheck and data that can't be deserialized exit with
+///   [`exitcode::DATAERR`]
+/// - I/O errors exit with [`exitcode::IOERR`]
+/// - any other error exits with `1`
+pub fn exit_code(error: &anyhow::Error) -> exitcode::ExitCode {
+    let exit_error = |error: &ExitError| match error {
+        ExitError::Collisions | ExitError::InvalidRules => exitcode::CONFIG,
+        ExitError::Healthcheck => exitcode::DATAERR,
+    };
+    for cause in error.chain() {
+        if let Some(error) = cause.downcast_ref::<ExitError>() {
+            return exit_error(error);
+        }
+        if let Some(Reported(error)) = cause.downcast_ref::<Reported>() {
+            return exit_error(error);
+        }
+        if let Some(error) = cause.downcast_ref::<serde_json::Error>() {
+            return if error.is_io() {
+                exitcode::IOERR
+            } else {
+                exitcode::DATAERR
+            };
+        }
+        if let Some(error) = cause.downcast_ref::<NbtError>() {
+            return if let NbtError::Io(_) = error {
+                exitcode::IOERR
+            } else {
+                exitcode::DATAERR
+            };
+        }
+        if cause.is::<std::io::Error>() {
+            return exitcode::IOERR;
+        }
+    }
+    1
+}
+
+/// Writes `value` as json to the output file, or to stdout if there is none.
+///
+/// Returns `false` if the user chose not to overwrite an existing file.
+pub(crate) fn write_json<T: Serialize>(output: Option<&OutputFile>, value: &T, pretty: bool) -> Result<bool> {
+    match output {
+        Some(output) => {
+            if let Some(writer) = output.writer()? {
+                if pretty {
+                    serde_json::to_writer_pretty(writer, value)?;
+                } else {
+                    serde_json::to_writer(writer, value)?;
+                }
+                Ok(true)
+            } else {
+                Ok(false)
+            }
+        },
+        None => {
+            let result = if pretty {
+                serde_json::to_string_pretty(value)?
+            } else {
+                serde_json::to_string(value)?
+            };
+            println!("{}", result);
+            eprintln!("========");
+            Ok(true)
+        },
+    }
+}
+
+/// Writes a json report to `report_output`, or to stdout if the data itself
+/// is written to `output`. Status messages stay on stderr either way.
+///
+/// Returns `false` if the user chose not to overwrite an existing file.
+pub(crate) fn write_report<T: Serialize>(output: Option<&OutputFile>, report_output: Option<&OutputFile>, report: &T) -> Result<bool> {
+    match (report_output, output) {
+        (Some(report_output), _) => write_json(Some(report_output), report, false),
+        (None, Some(_)) => {
+            println!("{}", serde_json::to_string(report)?);
+            Ok(true)
+        },
+        (None, None) => bail!("A json report needs --report-output or --output, stdout can't hold both the report and the data"),
+    }
+}
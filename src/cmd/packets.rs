@@ -0,0 +1,155 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use data_compat_mc::codegen::packets::PacketIdGenerator;
+use data_compat_mc::packets::{PacketList, RawPackets};
+use data_compat_mc::MetaData;
+
+use super::file::{InputFile, OutputFile};
+use super::ReportFormat;
+
+#[derive(Args, Debug)]
+/// Works with packet tables
+pub struct PacketsCommand {
+    #[clap(subcommand)]
+    command: PacketsSubCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PacketsSubCommands {
+    Compact(CompactCommand),
+    Diff(DiffCommand),
+    Codegen(PacketCodegenCommand),
+}
+
+#[derive(Args, Debug)]
+/// Generates a compact packet table
+///
+/// Fed with the `packets.json` report from the Minecraft generators (1.20.2+),
+/// this command will generate a compact list of packets for every protocol
+/// state and direction, in the order of their protocol ids.
+pub struct CompactCommand {
+    /// File containing the packets report from Minecraft
+    input: InputFile,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    /// The ID of the minecraft version the report comes from (e.g 2730)
+    #[clap(long)]
+    id: Option<i32>,
+    /// The pretty version number (e.g 1.17.1)
+    #[clap(short = 'd', long, requires = "id")]
+    display_name: Option<String>,
+    #[clap(long, requires = "id")]
+    note: Option<String>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+}
+
+#[derive(Args, Debug)]
+/// Shows which packets changed between two versions
+///
+/// Fed with two compact packet tables from mc-data, this command will list
+/// every packet that was added, removed or got another protocol id.
+pub struct DiffCommand {
+    /// File containing the packet table of the older version
+    from: InputFile,
+    /// File containing the packet table of the newer version
+    to: InputFile,
+    /// How to report the changes, `json` prints a machine-readable report to
+    /// stdout
+    #[clap(long, arg_enum, default_value = "text")]
+    report: ReportFormat,
+}
+
+#[derive(Args, Debug)]
+/// Generates Rust code from a packet table
+///
+/// Fed with a compact packet table from mc-data, this command will generate a
+/// Rust module containing a constant with the protocol id of every packet.
+pub struct PacketCodegenCommand {
+    /// File containing the packet table from mc-data
+    input: InputFile,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+}
+
+impl PacketsCommand {
+    pub fn execute(&self) -> Result<()> {
+        match &self.command {
+            PacketsSubCommands::Compact(cmd) => cmd.compact(),
+            PacketsSubCommands::Diff(cmd) => cmd.diff(),
+            PacketsSubCommands::Codegen(cmd) => cmd.generate_code(),
+        }
+    }
+}
+
+impl CompactCommand {
+    pub fn compact(&self) -> Result<()> {
+        let raw: RawPackets = self.input.deserialized()?;
+        let metadata = self
+            .id
+            .map(|id| MetaData::new(id, self.display_name.as_deref(), self.note.as_deref()));
+        let packets = PacketList::compact(metadata, &raw)?;
+
+        let count: usize = packets
+            .states
+            .values()
+            .flat_map(|directions| directions.values())
+            .map(Vec::len)
+            .sum();
+        eprintln!("Loaded {} packets in {} states \u{2705}", count, packets.states.len());
+
+        if super::write_json(self.output.as_ref(), &packets, !self.no_pretty)? {
+            eprintln!("Successfully compacted packets \u{2705}");
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+}
+
+impl DiffCommand {
+    pub fn diff(&self) -> Result<()> {
+        let from: PacketList = self.from.deserialized()?;
+        let to: PacketList = self.to.deserialized()?;
+
+        let changes = data_compat_mc::packets::diff(&from, &to);
+        match self.report {
+            ReportFormat::Json => println!("{}", serde_json::to_string(&changes)?),
+            ReportFormat::Text if changes.is_empty() => eprintln!("No packets changed \u{2705}"),
+            ReportFormat::Text => {
+                for change in &changes {
+                    println!("{}", change);
+                }
+                eprintln!("{} packets changed", changes.len());
+            },
+        }
+
+        Ok(())
+    }
+}
+
+impl PacketCodegenCommand {
+    pub fn generate_code(&self) -> Result<()> {
+        let data: PacketList = self.input.deserialized()?;
+
+        let code = PacketIdGenerator::new(&data).generate()?;
+
+        match &self.output {
+            Some(output) => {
+                if let Some(mut writer) = output.writer()? {
+                    writer.write_all(code.as_bytes())?;
+                    eprintln!("Successfully generated code \u{2705}");
+                } else {
+                    eprintln!("Aborted");
+                }
+            },
+            None => print!("{}", code),
+        }
+
+        Ok(())
+    }
+}
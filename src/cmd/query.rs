@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Result};
+use clap::Args;
+use data_compat_mc::{BlockStateString, ModernBlockList};
+
+use super::file::InputFile;
+
+#[derive(Args, Debug)]
+/// Looks up blockstates in intermediary data
+///
+/// Every query is either a blockstate id or a blockstate string like
+/// `minecraft:oak_stairs[facing=east,half=top]`. Ids get resolved to their
+/// full blockstate string and blockstate strings to their id. Properties that
+/// are left out take the value of the default blockstate.
+pub struct QueryCommand {
+    /// File containing intermediary data from mc-data
+    input: InputFile,
+    /// Blockstate ids or blockstate strings to look up
+    #[clap(required = true)]
+    queries: Vec<String>,
+}
+
+impl QueryCommand {
+    pub fn query(&self) -> Result<()> {
+        let data: ModernBlockList = self.input.deserialized()?;
+        let index = data.index()?;
+
+        for query in &self.queries {
+            if let Ok(id) = query.parse::<i32>() {
+                let state = index.state(id).ok_or_else(|| anyhow!("Unknown blockstate id {}", id))?;
+                println!("{} -> {}", id, state);
+            } else {
+                let state = BlockStateString::try_from(query.as_str()).map_err(|e| anyhow!("Invalid blockstate \"{}\": {}", query, e))?;
+                let id = index.resolve(&state)?;
+                println!("{} -> {}", state, id);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use clap::Args;
+use data_compat_mc::registries::{RawRegistries, RegistryList};
+use data_compat_mc::MetaData;
+
+use super::file::{InputFile, OutputFile};
+use super::ExitError;
+
+#[derive(Args, Debug)]
+/// Generates compact registry data
+///
+/// Fed with the `registries.json` report from the Minecraft generators, this
+/// command will generate a compact list of entries for every registry, in the
+/// order of their protocol ids. Registries with gaps or duplicates in their
+/// protocol ids fail the healthcheck.
+pub struct RegistriesCommand {
+    /// File containing the registries report from Minecraft
+    input: InputFile,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    /// The ID of the minecraft version the report comes from (e.g 2730)
+    #[clap(long)]
+    id: Option<i32>,
+    /// The pretty version number (e.g 1.17.1)
+    #[clap(short = 'd', long, requires = "id")]
+    display_name: Option<String>,
+    #[clap(long, requires = "id")]
+    note: Option<String>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+}
+
+impl RegistriesCommand {
+    pub fn compact_registries(&self) -> Result<()> {
+        let raw: RawRegistries = self.input.deserialized()?;
+        eprintln!("Loaded {} registries successfully \u{2705}", raw.len());
+
+        let issues = data_compat_mc::registries::healthcheck(&raw);
+        if !issues.is_empty() {
+            eprintln!("Healthcheck failed: \u{274C}");
+            for issue in &issues {
+                eprintln!("{}", issue);
+            }
+            return Err(ExitError::Healthcheck.into());
+        }
+        eprintln!("Healthcheck success! \u{2705}");
+
+        let metadata = self
+            .id
+            .map(|id| MetaData::new(id, self.display_name.as_deref(), self.note.as_deref()));
+        let registries = RegistryList::compact(metadata, &raw)?;
+
+        if super::write_json(self.output.as_ref(), &registries, !self.no_pretty)? {
+            eprintln!("Successfully compacted registries \u{2705}");
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+}
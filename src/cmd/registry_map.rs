@@ -0,0 +1,96 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use data_compat_mc::registries::mapping::{RegistryMapping, RegistryMappingRules};
+use data_compat_mc::registries::RegistryList;
+
+use super::file::{InputFile, OutputFile};
+
+#[derive(Args, Debug)]
+/// Generates registry id mappings between two versions
+///
+/// Fed with two compacted registry files from mc-data, this command will map
+/// the protocol id of every entry of every registry of the first version to the
+/// protocol id of the same entry in the second version. Renamed entries and
+/// fallbacks for entries without a counterpart can be specified in a rules
+/// file.
+pub struct RegistryMapCommand {
+    /// File containing compacted registries of the version to map from
+    from: InputFile,
+    /// File containing compacted registries of the version to map to
+    to: InputFile,
+    /// A file specifying renames and fallbacks per registry
+    #[clap(short, long)]
+    rules: Option<InputFile>,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+    /// Also writes every registry mapping as a binary array to this
+    /// directory, both files need metadata. The arrays use the binary format
+    /// of blockstate mappings, with protocol ids in place of blockstate ids
+    #[clap(long)]
+    binary: Option<PathBuf>,
+}
+
+impl RegistryMapCommand {
+    pub fn map_registries(&self) -> Result<()> {
+        let from: RegistryList = self.from.deserialized()?;
+        let to: RegistryList = self.to.deserialized()?;
+        let rules: Option<RegistryMappingRules> = self.rules.as_ref().map(|rules| rules.deserialized()).transpose()?;
+
+        let mapping = RegistryMapping::new(&from, &to, rules.as_ref())?;
+
+        for (identifier, registry) in &mapping.registries {
+            eprintln!(
+                "{}: mapped {} entries, {} using fallbacks, {} unmapped",
+                identifier,
+                registry.ids.len() - registry.unmapped.len(),
+                registry.fallbacks.len(),
+                registry.unmapped.len()
+            );
+        }
+        for identifier in &mapping.missing {
+            eprintln!("{} doesn't exist in the target version \u{26A0}\u{FE0F}", identifier);
+        }
+
+        if let Some(directory) = &self.binary {
+            self.write_binary(directory, &mapping)?;
+        }
+
+        if super::write_json(self.output.as_ref(), &mapping, !self.no_pretty)? {
+            eprintln!("Successfully generated mapping \u{2705}");
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+
+    fn write_binary(&self, directory: &Path, mapping: &RegistryMapping) -> Result<()> {
+        let (from, to) = mapping
+            .from
+            .as_ref()
+            .zip(mapping.to.as_ref())
+            .context("The binary mapping needs metadata for both versions")?;
+        std::fs::create_dir_all(directory)?;
+        let mut written = 0;
+        for (identifier, registry) in &mapping.registries {
+            let array = registry.array(from, to);
+            let name = format!("{}_{}.bin", identifier.namespace(), identifier.location().replace('/', "_"));
+            let output = OutputFile::from(directory.join(name));
+            match output.writer()? {
+                Some(mut writer) => {
+                    writer.write_all(&array.to_bytes(array.width())?)?;
+                    written += 1;
+                },
+                None => eprintln!("Skipped {}", identifier),
+            }
+        }
+        eprintln!("Successfully wrote {} binary mappings \u{2705}", written);
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use data_compat_mc::ModernPropertyRules;
+
+use super::file::{InputFile, OutputFile};
+use super::{ExitError, ReportFormat};
+
+#[derive(Args, Debug)]
+/// Works with rules files
+pub struct RulesCommand {
+    #[clap(subcommand)]
+    command: RulesSubCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RulesSubCommands {
+    Suggest(SuggestCommand),
+    Check(CheckCommand),
+}
+
+#[derive(Args, Debug)]
+/// Suggests a rules file resolving property name collisions
+///
+/// Fed with a raw data file from the Minecraft generators, this command will
+/// propose a unique name for every set of values sharing a property name with
+/// another set. The names are derived from the blocks using the values. Rules
+/// from an existing rules file are kept as they are.
+pub struct SuggestCommand {
+    /// File containing raw generated data from Minecraft
+    input: InputFile,
+    /// An existing rules file to extend
+    #[clap(short, long)]
+    rules: Option<InputFile>,
+    #[clap(short, long)]
+    output: Option<OutputFile>,
+    #[clap(long)]
+    /// Does not pretty-print the resulting json data
+    no_pretty: bool,
+}
+
+#[derive(Args, Debug)]
+/// Checks a rules file for mistakes
+///
+/// Fed with a raw data file from the Minecraft generators and a rules file,
+/// this command will report names and values that are specified more than
+/// once, rules that are never used or always overridden by more specific
+/// rules, rules causing new name collisions and rules that don't change
+/// anything.
+pub struct CheckCommand {
+    /// File containing raw generated data from Minecraft
+    input: InputFile,
+    /// The rules file to check
+    rules: InputFile,
+    /// How to report the issues, `json` prints a machine-readable report to
+    /// stdout
+    #[clap(long, arg_enum, default_value = "text")]
+    report: ReportFormat,
+}
+
+impl RulesCommand {
+    pub fn execute(&self) -> Result<()> {
+        match &self.command {
+            RulesSubCommands::Suggest(cmd) => cmd.suggest(),
+            RulesSubCommands::Check(cmd) => cmd.check(),
+        }
+    }
+}
+
+impl SuggestCommand {
+    pub fn suggest(&self) -> Result<()> {
+        let data = self.input.data();
+        let rules: Option<ModernPropertyRules> = self.rules.as_ref().map(|rules| rules.deserialized()).transpose()?;
+
+        let collisions = data_compat_mc::check_collisions(data, rules.as_ref())?;
+        if !collisions.should_exit() {
+            eprintln!("No name collisions found, no rules need to be added \u{2705}");
+        }
+        let suggested = data_compat_mc::suggest_rules(data, rules.as_ref(), &collisions)?;
+        for (name, values) in suggested.suggested() {
+            eprintln!("{} -> {:?}", name, values.fields());
+        }
+
+        // Make sure the suggestion actually resolves the collisions
+        let suggested_data = serde_json::to_string(&suggested)?;
+        let suggested_rules = data_compat_mc::load_rules(&suggested_data)?;
+        if data_compat_mc::check_collisions(data, Some(&suggested_rules))?.should_exit() {
+            eprintln!("The suggested rules do not resolve all collisions, the existing rules may assign the same name twice \u{274C}");
+            return Err(ExitError::Collisions.into());
+        }
+
+        if super::write_json(self.output.as_ref(), &suggested, !self.no_pretty)? {
+            eprintln!("Successfully suggested {} rules \u{2705}", suggested.suggested().len());
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+}
+
+impl CheckCommand {
+    pub fn check(&self) -> Result<()> {
+        let issues = data_compat_mc::validate_rules(self.input.data(), self.rules.data())?;
+
+        match self.report {
+            ReportFormat::Json => println!("{}", serde_json::to_string(&issues)?),
+            ReportFormat::Text if issues.is_empty() => eprintln!("No issues found \u{2705}"),
+            ReportFormat::Text => {
+                for issue in &issues {
+                    eprintln!("{}", issue);
+                }
+                eprintln!("Found {} issues! \u{274C}", issues.len());
+            },
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ExitError::InvalidRules.into())
+        }
+    }
+}
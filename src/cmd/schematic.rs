@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Subcommand};
+use data_compat_mc::blocks::mapping::{MappingRules, StateMapper};
+use data_compat_mc::schematic::{SchematicConverter, SpongeSchematic};
+use data_compat_mc::{BlockStateString, ModernBlockList};
+
+use super::file::{InputFile, OutputFile};
+
+#[derive(Args, Debug)]
+/// Works with Sponge schematic files
+pub struct SchematicCommand {
+    #[clap(subcommand)]
+    command: SchematicSubCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SchematicSubCommands {
+    Convert(ConvertCommand),
+}
+
+#[derive(Args, Debug)]
+/// Converts a schematic between Minecraft versions
+///
+/// Fed with a Sponge schematic (version 2 or 3) and the intermediary data
+/// files of both versions, this command will remap every palette entry to the
+/// equivalent blockstate of the target version. Palette entries without a
+/// counterpart are replaced by a fallback blockstate and reported.
+pub struct ConvertCommand {
+    /// The schematic file to convert
+    input: PathBuf,
+    /// File containing intermediary data of the version the schematic uses
+    #[clap(long)]
+    from: InputFile,
+    /// File containing intermediary data of the version to convert to
+    #[clap(long)]
+    to: InputFile,
+    /// A file specifying how blocks and values changed between the versions
+    #[clap(short, long)]
+    rules: Option<InputFile>,
+    /// The file to write the converted schematic to
+    #[clap(short, long)]
+    output: OutputFile,
+    /// Blockstate used for palette entries that can't be converted
+    #[clap(long, default_value = "minecraft:air")]
+    fallback: String,
+    /// The data version of the target version, defaults to the id in the
+    /// target's metadata
+    #[clap(long)]
+    data_version: Option<i32>,
+}
+
+impl SchematicCommand {
+    pub fn execute(&self) -> Result<()> {
+        match &self.command {
+            SchematicSubCommands::Convert(cmd) => cmd.convert(),
+        }
+    }
+}
+
+impl ConvertCommand {
+    pub fn convert(&self) -> Result<()> {
+        let from: ModernBlockList = self.from.deserialized()?;
+        let to: ModernBlockList = self.to.deserialized()?;
+        let data_version = self
+            .data_version
+            .or_else(|| to.metadata.as_ref().map(|meta| meta.id))
+            .ok_or_else(|| anyhow!("The target data version is unknown, please specify it using --data-version"))?;
+
+        let from_index = from.index()?;
+        let to_index = to.index()?;
+        let fallback = BlockStateString::try_from(self.fallback.as_str()).map_err(|e| anyhow!("Invalid fallback blockstate: {}", e))?;
+        let fallback = to_index.resolve(&fallback)?;
+        let rules: Option<MappingRules> = self.rules.as_ref().map(|rules| rules.deserialized()).transpose()?;
+        let mapping = StateMapper::new(&from, &to, rules.as_ref()).map()?;
+
+        let data = std::fs::read(&self.input).with_context(|| format!("Could not read {:?}", self.input))?;
+        let mut schematic = SpongeSchematic::read(&data)?;
+        let unmapped = SchematicConverter::new(&from_index, &to_index, &mapping, fallback).convert(&mut schematic)?;
+        schematic.set_data_version(data_version);
+
+        if !unmapped.is_empty() {
+            eprintln!("The following palette entries could not be converted: \u{26A0}\u{FE0F}");
+            for (entry, reason) in &unmapped {
+                eprintln!("{} -> {}", entry, reason);
+            }
+            eprintln!("========");
+        }
+
+        if let Some(writer) = self.output.writer()? {
+            schematic.write(writer)?;
+            eprintln!("Successfully converted schematic \u{2705}");
+        } else {
+            eprintln!("Aborted");
+        }
+
+        Ok(())
+    }
+}
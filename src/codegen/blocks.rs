@@ -0,0 +1,330 @@
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use super::{field_name, type_name};
+use crate::blocks::intermediary::data::{ModernBlockData, ModernBlockList, PropertyValue};
+use crate::util::identifier::Identifier;
+
+#[derive(Debug, Error)]
+pub enum CodegenError {
+    #[error("Unknown property \"{property}\" for block \"{block}\"")]
+    UnknownProperty {
+        block: String,
+        property: String,
+    },
+    #[error("Could not write generated code")]
+    Fmt(#[from] std::fmt::Error),
+}
+
+/// Generates a Rust module representing every blockstate in the compact
+/// format.
+///
+/// The generated module contains:
+/// - a `properties` module with an enum for every enum property
+/// - a struct for every block that has one or more properties
+/// - a `BlockState` enum with a variant for every block, together with
+///   `to_state_id` and `from_state_id` functions
+///
+/// Integer properties are plain `u8` fields, `to_state_id` returns `None` if
+/// one of them is outside of the range of the property.
+///
+/// Blockstate ids are calculated from the base id of each block plus an
+/// offset derived from its property values, where the first property is the
+/// most significant one.
+pub struct BlockStateGenerator<'a, 'raw> {
+    data: &'a ModernBlockList<'raw>,
+}
+
+/// A block together with the precalculated information needed to generate
+/// its code.
+struct Block<'a, 'raw> {
+    name: String,
+    identifier: Identifier<'raw>,
+    data: &'a ModernBlockData<'raw>,
+    lengths: Vec<usize>,
+    state_count: usize,
+}
+
+impl<'a, 'raw> BlockStateGenerator<'a, 'raw> {
+    pub fn new(data: &'a ModernBlockList<'raw>) -> Self { Self { data } }
+
+    pub fn generate(&self) -> Result<String, CodegenError> {
+        let blocks = self
+            .data
+            .blocks
+            .iter()
+            .map(|(identifier, data)| {
+                let lengths = data
+                    .property_values(&self.data.properties)
+                    .map_err(|property| CodegenError::UnknownProperty {
+                        block: identifier.to_string(),
+                        property: property.to_string(),
+                    })?
+                    .iter()
+                    .map(|(_, values)| values.len())
+                    .collect::<Vec<_>>();
+                Ok(Block {
+                    name: block_name(identifier),
+                    identifier: *identifier,
+                    data,
+                    state_count: lengths.iter().product(),
+                    lengths,
+                })
+            })
+            .collect::<Result<Vec<_>, CodegenError>>()?;
+
+        let mut out = String::new();
+        writeln!(out, "//! Blockstates of Minecraft")?;
+        if let Some(meta) = &self.data.metadata {
+            writeln!(out, "//!")?;
+            match meta.name {
+                Some(name) => writeln!(out, "//! Version {} ({})", meta.id, name)?,
+                None => writeln!(out, "//! Version {}", meta.id)?,
+            }
+            if let Some(note) = meta.note {
+                writeln!(out, "//! Note: {}", note)?;
+            }
+        }
+        writeln!(out, "//!\n//! This file was generated by mc-data, do not edit it manually.\n")?;
+
+        self.generate_properties(&mut out)?;
+        for block in &blocks {
+            if !block.data.kinds.is_empty() {
+                generate_block(&mut out, block)?;
+            }
+        }
+        generate_states(&mut out, &blocks)?;
+
+        Ok(out)
+    }
+
+    fn generate_properties(&self, out: &mut String) -> Result<(), CodegenError> {
+        writeln!(out, "pub mod properties {{")?;
+        for (index, (name, property)) in self.data.properties.iter().enumerate() {
+            let name = type_name(name);
+            if index != 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]")?;
+            writeln!(out, "    pub enum {} {{", name)?;
+            for value in property.fields() {
+                writeln!(out, "        {},", type_name(value))?;
+            }
+            writeln!(out, "    }}\n")?;
+
+            writeln!(out, "    impl {} {{", name)?;
+            writeln!(out, "        pub const COUNT: u32 = {};\n", property.fields().len())?;
+            writeln!(out, "        pub const fn index(self) -> u32 {{ self as u32 }}\n")?;
+            writeln!(out, "        pub const fn from_index(index: u32) -> Option<Self> {{")?;
+            writeln!(out, "            match index {{")?;
+            for (index, value) in property.fields().iter().enumerate() {
+                writeln!(out, "                {} => Some(Self::{}),", index, type_name(value))?;
+            }
+            writeln!(out, "                _ => None,")?;
+            writeln!(out, "            }}")?;
+            writeln!(out, "        }}\n")?;
+            writeln!(out, "        pub const fn as_str(self) -> &'static str {{")?;
+            writeln!(out, "            match self {{")?;
+            for value in property.fields() {
+                writeln!(out, "                Self::{} => \"{}\",", type_name(value), value)?;
+            }
+            writeln!(out, "            }}")?;
+            writeln!(out, "        }}")?;
+            writeln!(out, "    }}")?;
+        }
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+}
+
+fn generate_block(out: &mut String, block: &Block) -> Result<(), CodegenError> {
+    let name = &block.name;
+    let kinds = &block.data.kinds;
+
+    writeln!(out, "\n#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]")?;
+    writeln!(out, "pub struct {} {{", name)?;
+    for (property, kind) in kinds {
+        let kind = match kind {
+            PropertyValue::Range(_) => "u8".to_string(),
+            PropertyValue::Text("bool") => "bool".to_string(),
+            PropertyValue::Text(text) => format!("properties::{}", type_name(text)),
+        };
+        writeln!(out, "    pub {}: {},", field_name(property), kind)?;
+    }
+    writeln!(out, "}}\n")?;
+
+    writeln!(out, "impl {} {{", name)?;
+    writeln!(out, "    pub const BASE_ID: u32 = {};", block.data.base_id)?;
+    writeln!(out, "    pub const STATE_COUNT: u32 = {};\n", block.state_count)?;
+
+    writeln!(out, "    /// The offset from [`Self::BASE_ID`], `None` if an integer property is")?;
+    writeln!(out, "    /// out of range.")?;
+    writeln!(out, "    pub const fn offset(self) -> Option<u32> {{")?;
+    writeln!(out, "        let mut offset = 0;")?;
+    for ((property, kind), len) in kinds.iter().zip(&block.lengths) {
+        let field = field_name(property);
+        match kind {
+            PropertyValue::Range([start, end]) => {
+                let out_of_range = match (*start, *end) {
+                    (0, u8::MAX) => None,
+                    (0, end) => Some(format!("self.{} > {}", field, end)),
+                    (start, u8::MAX) => Some(format!("self.{} < {}", field, start)),
+                    (start, end) => Some(format!("self.{} < {} || self.{} > {}", field, start, field, end)),
+                };
+                if let Some(condition) = out_of_range {
+                    writeln!(out, "        if {} {{", condition)?;
+                    writeln!(out, "            return None;")?;
+                    writeln!(out, "        }}")?;
+                }
+                match start {
+                    0 => writeln!(out, "        offset = offset * {} + self.{} as u32;", len, field)?,
+                    start => writeln!(out, "        offset = offset * {} + (self.{} - {}) as u32;", len, field, start)?,
+                }
+            },
+            PropertyValue::Text("bool") => writeln!(out, "        offset = offset * 2 + if self.{} {{ 0 }} else {{ 1 }};", field)?,
+            PropertyValue::Text(_) => writeln!(out, "        offset = offset * {} + self.{}.index();", len, field)?,
+        }
+    }
+    writeln!(out, "        Some(offset)")?;
+    writeln!(out, "    }}\n")?;
+
+    writeln!(out, "    pub const fn from_offset(offset: u32) -> Option<Self> {{")?;
+    writeln!(out, "        if offset >= Self::STATE_COUNT {{")?;
+    writeln!(out, "            return None;")?;
+    writeln!(out, "        }}")?;
+    if kinds.len() > 1 {
+        writeln!(out, "        let mut offset = offset;")?;
+    }
+    for (index, ((property, kind), len)) in kinds.iter().zip(&block.lengths).enumerate().rev() {
+        let field = field_name(property);
+        match kind {
+            PropertyValue::Range([0, _]) => writeln!(out, "        let {} = (offset % {}) as u8;", field, len)?,
+            PropertyValue::Range([start, _]) => writeln!(out, "        let {} = {} + (offset % {}) as u8;", field, start, len)?,
+            PropertyValue::Text("bool") => writeln!(out, "        let {} = offset.is_multiple_of(2);", field)?,
+            PropertyValue::Text(text) => {
+                writeln!(out, "        let {} = match properties::{}::from_index(offset % {}) {{", field, type_name(text), len)?;
+                writeln!(out, "            Some(value) => value,")?;
+                writeln!(out, "            None => return None,")?;
+                writeln!(out, "        }};")?;
+            },
+        }
+        if index != 0 {
+            writeln!(out, "        offset /= {};", len)?;
+        }
+    }
+    writeln!(out, "        Some(Self {{")?;
+    for (property, _) in kinds {
+        writeln!(out, "            {},", field_name(property))?;
+    }
+    writeln!(out, "        }})")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}\n")?;
+
+    writeln!(out, "impl Default for {} {{", name)?;
+    writeln!(out, "    fn default() -> Self {{")?;
+    writeln!(out, "        match Self::from_offset({}) {{", block.data.default_id() - block.data.base_id)?;
+    writeln!(out, "            Some(state) => state,")?;
+    writeln!(out, "            None => unreachable!(),")?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn generate_states(out: &mut String, blocks: &[Block]) -> Result<(), CodegenError> {
+    writeln!(out, "\n#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]")?;
+    writeln!(out, "pub enum BlockState {{")?;
+    for block in blocks {
+        if block.data.kinds.is_empty() {
+            writeln!(out, "    {},", block.name)?;
+        } else {
+            writeln!(out, "    {}({}),", block.name, block.name)?;
+        }
+    }
+    writeln!(out, "}}\n")?;
+
+    writeln!(out, "impl BlockState {{")?;
+    writeln!(out, "    /// The blockstate id, `None` if an integer property is out of range.")?;
+    writeln!(out, "    pub const fn to_state_id(self) -> Option<u32> {{")?;
+    writeln!(out, "        let (base_id, offset) = match self {{")?;
+    for block in blocks {
+        if block.data.kinds.is_empty() {
+            writeln!(out, "            BlockState::{} => return Some({}),", block.name, block.data.base_id)?;
+        } else {
+            writeln!(out, "            BlockState::{}(state) => ({}::BASE_ID, state.offset()),", block.name, block.name)?;
+        }
+    }
+    writeln!(out, "        }};")?;
+    writeln!(out, "        match offset {{")?;
+    writeln!(out, "            Some(offset) => Some(base_id + offset),")?;
+    writeln!(out, "            None => None,")?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}\n")?;
+
+    writeln!(out, "    pub const fn from_state_id(id: u32) -> Option<Self> {{")?;
+    writeln!(out, "        match id {{")?;
+    for block in blocks {
+        if block.data.kinds.is_empty() {
+            writeln!(out, "            {} => Some(BlockState::{}),", block.data.base_id, block.name)?;
+        } else {
+            let last_id = block.data.base_id as usize + block.state_count - 1;
+            writeln!(out, "            {}..={} => match {}::from_offset(id - {}::BASE_ID) {{", block.data.base_id, last_id, block.name, block.name)?;
+            writeln!(out, "                Some(state) => Some(BlockState::{}(state)),", block.name)?;
+            writeln!(out, "                None => None,")?;
+            writeln!(out, "            }},")?;
+        }
+    }
+    writeln!(out, "            _ => None,")?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}\n")?;
+
+    writeln!(out, "    pub const fn identifier(&self) -> &'static str {{")?;
+    writeln!(out, "        match self {{")?;
+    for block in blocks {
+        if block.data.kinds.is_empty() {
+            writeln!(out, "            BlockState::{} => \"{}\",", block.name, block.identifier)?;
+        } else {
+            writeln!(out, "            BlockState::{}(_) => \"{}\",", block.name, block.identifier)?;
+        }
+    }
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Returns the type name of a block, only blocks outside of the `minecraft`
+/// namespace get their namespace prepended.
+fn block_name(identifier: &Identifier) -> String {
+    if identifier.namespace() == "minecraft" {
+        type_name(identifier.location())
+    } else {
+        format!("{}{}", type_name(identifier.namespace()), type_name(identifier.location()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockStateGenerator;
+    use crate::blocks::intermediary::data::ModernBlockList;
+
+    #[test]
+    fn test_range_bounds() {
+        let data: ModernBlockList = serde_json::from_str(
+            r#"{
+                "properties": {},
+                "blocks": {
+                    "minecraft:cake": { "properties": { "bites": [1, 6] }, "base": 0 },
+                    "minecraft:light": { "properties": { "level": [0, 15] }, "base": 6 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let code = BlockStateGenerator::new(&data).generate().unwrap();
+        assert!(code.contains(
+            "        if self.bites < 1 || self.bites > 6 {\n            return None;\n        }\n        offset = offset * 6 + (self.bites - 1) as u32;\n"
+        ));
+        assert!(code.contains("        if self.level > 15 {\n            return None;\n        }\n        offset = offset * 16 + self.level as u32;\n"));
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt::Write;
+
+use crate::blocks::mapping::{ArrayWidth, MappingArray, HEADER_SIZE};
+
+/// Generates a Rust module embedding a binary [`MappingArray`] using
+/// `include_bytes!`.
+///
+/// The generated module contains:
+/// - `FROM_VERSION` and `TO_VERSION` with the ids of both versions
+/// - `MAPPING`, the binary mapping including its header
+/// - a `map_state` function translating a blockstate id without parsing
+///   anything at runtime
+///
+/// `path` is the path of the binary file, relative to the generated file.
+pub struct MappingArrayGenerator<'a> {
+    array: &'a MappingArray,
+    width: ArrayWidth,
+    path: &'a str,
+}
+
+impl<'a> MappingArrayGenerator<'a> {
+    pub fn new(array: &'a MappingArray, width: ArrayWidth, path: &'a str) -> Self { Self { array, width, path } }
+
+    pub fn generate(&self) -> Result<String, std::fmt::Error> {
+        let (kind, bytes, value) = match self.width {
+            ArrayWidth::U16 => ("u16", "[bytes[0], bytes[1]]", "value as u32"),
+            ArrayWidth::U32 => ("u32", "[bytes[0], bytes[1], bytes[2], bytes[3]]", "value"),
+        };
+
+        let mut out = String::new();
+        writeln!(out, "//! Blockstate mapping from version {} to version {}", self.array.from, self.array.to)?;
+        writeln!(out, "//!\n//! This file was generated by mc-data, do not edit it manually.\n")?;
+        writeln!(out, "pub const FROM_VERSION: i32 = {};", self.array.from)?;
+        writeln!(out, "pub const TO_VERSION: i32 = {};", self.array.to)?;
+        writeln!(out, "pub const STATE_COUNT: u32 = {};\n", self.array.states.len())?;
+        writeln!(out, "const HEADER_SIZE: usize = {};\n", HEADER_SIZE)?;
+        writeln!(out, "/// The binary mapping, little-endian `{}` entries after a header", kind)?;
+        writeln!(out, "pub static MAPPING: &[u8] = include_bytes!({:?});\n", self.path)?;
+
+        writeln!(out, "/// Translates a blockstate id, returns `None` if it has no counterpart.")?;
+        writeln!(out, "pub fn map_state(id: u32) -> Option<u32> {{")?;
+        writeln!(out, "    let start = HEADER_SIZE + id as usize * {};", self.width.bytes())?;
+        writeln!(out, "    let bytes = MAPPING.get(start..start + {})?;", self.width.bytes())?;
+        writeln!(out, "    let value = {}::from_le_bytes({});", kind, bytes)?;
+        writeln!(out, "    if value == {}::MAX {{", kind)?;
+        writeln!(out, "        None")?;
+        writeln!(out, "    }} else {{")?;
+        writeln!(out, "        Some({})", value)?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")?;
+
+        Ok(out)
+    }
+}
@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+
+pub mod blocks;
+pub mod mapping;
+pub mod packets;
+
+/// Keywords that can't be used as identifiers in generated code without
+/// turning them into raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static", "struct", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords that can't be raw identifiers either, they get an `_` suffix
+/// instead.
+const RESERVED: &[&str] = &["crate", "self", "super"];
+
+/// Converts a `snake_case` (or otherwise separated) name into an
+/// `UpperCamelCase` type name.
+///
+/// Names starting with a digit get an `N` prefix to keep them valid
+/// identifiers.
+pub fn type_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if upper {
+                result.push(c.to_ascii_uppercase());
+            } else {
+                result.push(c);
+            }
+            upper = c.is_ascii_digit();
+        } else {
+            upper = true;
+        }
+    }
+    if result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, 'N');
+    }
+    result
+}
+
+/// Converts a name into a valid `snake_case` field name, using a raw
+/// identifier for keywords and an `_` suffix for `crate`, `self` and
+/// `super`.
+pub fn field_name(name: &str) -> Cow<'_, str> {
+    let valid =
+        name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_') && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        let mut result: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if !result.starts_with(|c: char| c.is_ascii_lowercase() || c == '_') {
+            result.insert(0, '_');
+        }
+        Cow::Owned(result)
+    } else if RESERVED.contains(&name) {
+        Cow::Owned(format!("{}_", name))
+    } else if KEYWORDS.contains(&name) {
+        Cow::Owned(format!("r#{}", name))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(type_name("oak_stairs"), "OakStairs");
+        assert_eq!(type_name("north_south"), "NorthSouth");
+        assert_eq!(type_name("1x1"), "N1X1");
+        assert_eq!(type_name("stage_2"), "Stage2");
+    }
+
+    #[test]
+    fn test_field_name() {
+        assert_eq!(field_name("waterlogged"), "waterlogged");
+        assert_eq!(field_name("type"), "r#type");
+        assert_eq!(field_name("crate"), "crate_");
+        assert_eq!(field_name("super"), "super_");
+        assert_eq!(field_name("2d"), "_2d");
+        assert_eq!(field_name("some-name"), "some_name");
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::Write;
+
+use ahash::AHashMap;
+use thiserror::Error;
+
+use super::field_name;
+use crate::packets::PacketList;
+
+#[derive(Debug, Error)]
+pub enum PacketCodegenError {
+    #[error("Packets \"{first}\" and \"{second}\" in {state}/{direction} would both become the constant {constant}")]
+    DuplicateConstant {
+        state: String,
+        direction: String,
+        constant: String,
+        first: String,
+        second: String,
+    },
+    #[error("Could not write generated code")]
+    Fmt(#[from] std::fmt::Error),
+}
+
+/// Generates a Rust module with a constant for the protocol id of every
+/// packet.
+///
+/// The generated module contains a module for every protocol state, which
+/// contains a module for every direction, e.g.
+/// `play::clientbound::ADD_ENTITY`.
+pub struct PacketIdGenerator<'a, 'raw> {
+    data: &'a PacketList<'raw>,
+}
+
+impl<'a, 'raw> PacketIdGenerator<'a, 'raw> {
+    pub fn new(data: &'a PacketList<'raw>) -> Self { Self { data } }
+
+    pub fn generate(&self) -> Result<String, PacketCodegenError> {
+        let mut out = String::new();
+        writeln!(out, "//! Packet ids of Minecraft")?;
+        if let Some(meta) = &self.data.metadata {
+            writeln!(out, "//!")?;
+            match meta.name {
+                Some(name) => writeln!(out, "//! Version {} ({})", meta.id, name)?,
+                None => writeln!(out, "//! Version {}", meta.id)?,
+            }
+            if let Some(note) = meta.note {
+                writeln!(out, "//! Note: {}", note)?;
+            }
+        }
+        writeln!(out, "//!\n//! This file was generated by mc-data, do not edit it manually.")?;
+
+        for (state, directions) in &self.data.states {
+            writeln!(out, "\npub mod {} {{", field_name(state))?;
+            for (index, (direction, packets)) in directions.iter().enumerate() {
+                if index != 0 {
+                    writeln!(out)?;
+                }
+                writeln!(out, "    pub mod {} {{", field_name(direction))?;
+                let mut constants = AHashMap::new();
+                for (id, packet) in packets.iter().enumerate() {
+                    let constant = constant_name(packet.namespace(), packet.location());
+                    if let Some(first) = constants.insert(constant.clone(), packet) {
+                        return Err(PacketCodegenError::DuplicateConstant {
+                            state: state.to_string(),
+                            direction: direction.to_string(),
+                            constant,
+                            first: first.to_string(),
+                            second: packet.to_string(),
+                        });
+                    }
+                    writeln!(out, "        pub const {}: i32 = {:#04x};", constant, id)?;
+                }
+                writeln!(out, "    }}")?;
+            }
+            writeln!(out, "}}")?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Converts the identifier of a packet into an `UPPER_SNAKE_CASE` constant
+/// name, packets outside of the `minecraft` namespace are prefixed by their
+/// namespace.
+fn constant_name(namespace: &str, location: &str) -> String {
+    let name = if namespace == "minecraft" {
+        location.to_string()
+    } else {
+        format!("{}_{}", namespace, location)
+    };
+    let mut result: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{constant_name, PacketCodegenError, PacketIdGenerator};
+    use crate::packets::PacketList;
+
+    #[test]
+    fn test_constant_name() {
+        assert_eq!(constant_name("minecraft", "add_entity"), "ADD_ENTITY");
+        assert_eq!(constant_name("falcon", "custom/payload"), "FALCON_CUSTOM_PAYLOAD");
+    }
+
+    #[test]
+    fn test_generate() {
+        let list: PacketList = serde_json::from_str(r#"{ "states": { "play": { "clientbound": ["bundle_delimiter", "add_entity"] } } }"#).unwrap();
+        let code = PacketIdGenerator::new(&list).generate().unwrap();
+        assert!(code.contains(
+            "pub mod play {\n    pub mod clientbound {\n        pub const BUNDLE_DELIMITER: i32 = 0x00;\n        pub const ADD_ENTITY: i32 = 0x01;\n"
+        ));
+
+        let list: PacketList = serde_json::from_str(r#"{ "states": { "play": { "serverbound": ["custom/payload", "custom_payload"] } } }"#).unwrap();
+        let error = PacketIdGenerator::new(&list).generate().unwrap_err();
+        assert!(matches!(error, PacketCodegenError::DuplicateConstant { constant, .. } if constant == "CUSTOM_PAYLOAD"));
+    }
+}
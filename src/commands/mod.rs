@@ -0,0 +1,551 @@
+//! The command tree from the `commands.json` report of the Minecraft
+//! generators, as sent to the client in the Declare Commands packet.
+use std::fmt::{Display, Formatter};
+
+use ahash::{AHashMap, RandomState};
+use hashlink::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::registries::Registry;
+use crate::util::identifier::Identifier;
+
+/// Node type of the root node in the flags of a [`FlatNode`].
+pub const FLAG_ROOT: u8 = 0x00;
+/// Node type of literal nodes in the flags of a [`FlatNode`].
+pub const FLAG_LITERAL: u8 = 0x01;
+/// Node type of argument nodes in the flags of a [`FlatNode`].
+pub const FLAG_ARGUMENT: u8 = 0x02;
+/// Set in the flags of a [`FlatNode`] if the command can be executed at this
+/// node.
+pub const FLAG_EXECUTABLE: u8 = 0x04;
+/// Set in the flags of a [`FlatNode`] if the node redirects to another node.
+pub const FLAG_REDIRECT: u8 = 0x08;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CommandError {
+    #[error("Argument node without a parser")]
+    MissingParser,
+    #[error("Node \"{node}\" redirects to the unknown node \"{redirect}\"")]
+    UnknownRedirect {
+        node: String,
+        redirect: String,
+    },
+    #[error("Node \"{node}\" uses the parser \"{parser}\", which isn't part of the registry")]
+    UnknownParser {
+        node: String,
+        parser: String,
+    },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    Root,
+    Literal,
+    Argument,
+}
+
+/// What a node matches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind<'raw> {
+    Root,
+    /// A literal matching the name of the node
+    Literal,
+    /// An argument parsed by `parser`, configured with `properties`
+    Argument {
+        parser: Identifier<'raw>,
+        properties: Option<Value>,
+    },
+}
+
+impl Display for NodeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeType::Root => write!(f, "root"),
+            NodeType::Literal => write!(f, "literal"),
+            NodeType::Argument => write!(f, "argument"),
+        }
+    }
+}
+
+impl<'raw> NodeKind<'raw> {
+    pub fn node_type(&self) -> NodeType {
+        match self {
+            NodeKind::Root => NodeType::Root,
+            NodeKind::Literal => NodeType::Literal,
+            NodeKind::Argument { .. } => NodeType::Argument,
+        }
+    }
+}
+
+/// A node of the command tree.
+///
+/// The root node of the `commands.json` report is a `CommandNode` as well.
+/// Redirects are the path of the target node from the root node, redirects to
+/// the root node itself are not part of the report: a node that can't be
+/// executed and has neither children nor a redirect, like `execute run`,
+/// implicitly redirects to the root node.
+#[derive(Debug)]
+pub struct CommandNode<'raw> {
+    pub kind: NodeKind<'raw>,
+    pub executable: bool,
+    pub redirect: Option<Vec<&'raw str>>,
+    pub children: LinkedHashMap<&'raw str, CommandNode<'raw>, RandomState>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCommandNode<'raw> {
+    #[serde(rename = "type")]
+    node_type: NodeType,
+    #[serde(borrow, default)]
+    children: LinkedHashMap<&'raw str, CommandNode<'raw>, RandomState>,
+    #[serde(default)]
+    executable: bool,
+    #[serde(borrow, default)]
+    redirect: Option<Vec<&'raw str>>,
+    #[serde(borrow, default)]
+    parser: Option<Identifier<'raw>>,
+    #[serde(default)]
+    properties: Option<Value>,
+}
+
+impl<'de: 'raw, 'raw> Deserialize<'de> for CommandNode<'raw> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = RawCommandNode::deserialize(deserializer)?;
+        let kind = match raw.node_type {
+            NodeType::Root => NodeKind::Root,
+            NodeType::Literal => NodeKind::Literal,
+            NodeType::Argument => NodeKind::Argument {
+                parser: raw.parser.ok_or_else(|| serde::de::Error::custom(CommandError::MissingParser))?,
+                properties: raw.properties,
+            },
+        };
+        Ok(Self {
+            kind,
+            executable: raw.executable,
+            redirect: raw.redirect,
+            children: raw.children,
+        })
+    }
+}
+
+impl<'raw> CommandNode<'raw> {
+    /// Returns the node at the given path from this node.
+    pub fn get(&self, path: &[&str]) -> Option<&CommandNode<'raw>> { path.iter().try_fold(self, |node, name| node.children.get(*name)) }
+
+    /// Whether this node implicitly redirects to the root node, which the
+    /// report leaves out.
+    pub fn redirects_to_root(&self) -> bool { !matches!(self.kind, NodeKind::Root) && !self.executable && self.children.is_empty() && self.redirect.is_none() }
+
+    /// Counts this node and all of its descendants.
+    pub fn count(&self) -> usize { 1 + self.children.values().map(CommandNode::count).sum::<usize>() }
+
+    /// Flattens the tree into the node array of the Declare Commands packet,
+    /// with this node as root at index 0.
+    ///
+    /// Nodes are numbered breadth-first. Parser ids are looked up in
+    /// `parsers`, the `minecraft:command_argument_type` registry, if given.
+    /// Nodes that implicitly redirect to the root node get an explicit
+    /// redirect to index 0, as the packet requires.
+    pub fn flatten(&self, parsers: Option<&Registry<'raw>>) -> Result<FlatCommands<'raw>, CommandError> {
+        let mut queue: Vec<(Option<&'raw str>, &CommandNode<'raw>, Vec<&'raw str>)> = vec![(None, self, Vec::new())];
+        let mut children = Vec::new();
+        let mut index = 0;
+        while let Some((_, node, path)) = queue.get(index) {
+            let (node, path) = (*node, path.clone());
+            let start = queue.len() as i32;
+            children.push((start..start + node.children.len() as i32).collect::<Vec<_>>());
+            for (&name, child) in &node.children {
+                let mut child_path = path.clone();
+                child_path.push(name);
+                queue.push((Some(name), child, child_path));
+            }
+            index += 1;
+        }
+
+        let indices: AHashMap<&[&str], i32> = queue
+            .iter()
+            .enumerate()
+            .map(|(index, (_, _, path))| (path.as_slice(), index as i32))
+            .collect();
+        let nodes = queue
+            .iter()
+            .zip(children)
+            .map(|((name, node, path), children)| {
+                let redirect = node
+                    .redirect
+                    .as_ref()
+                    .map(|redirect| {
+                        indices
+                            .get(redirect.as_slice())
+                            .copied()
+                            .ok_or_else(|| CommandError::UnknownRedirect {
+                                node: path.join(" "),
+                                redirect: redirect.join(" "),
+                            })
+                    })
+                    .transpose()?
+                    .or_else(|| node.redirects_to_root().then_some(0));
+                let (parser, parser_id, properties) = match &node.kind {
+                    NodeKind::Argument { parser, properties } => {
+                        let id = parsers
+                            .map(|parsers| {
+                                parsers.id(parser).ok_or_else(|| CommandError::UnknownParser {
+                                    node: path.join(" "),
+                                    parser: parser.to_string(),
+                                })
+                            })
+                            .transpose()?;
+                        (Some(*parser), id, properties.clone())
+                    },
+                    _ => (None, None, None),
+                };
+
+                let mut flags = match node.kind.node_type() {
+                    NodeType::Root => FLAG_ROOT,
+                    NodeType::Literal => FLAG_LITERAL,
+                    NodeType::Argument => FLAG_ARGUMENT,
+                };
+                if node.executable {
+                    flags |= FLAG_EXECUTABLE;
+                }
+                if redirect.is_some() {
+                    flags |= FLAG_REDIRECT;
+                }
+                Ok(FlatNode {
+                    flags,
+                    children,
+                    redirect,
+                    name: *name,
+                    parser,
+                    parser_id,
+                    properties,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(FlatCommands { root: 0, nodes })
+    }
+}
+
+/// A node of the Declare Commands packet.
+#[derive(Debug, Serialize)]
+pub struct FlatNode<'raw> {
+    pub flags: u8,
+    pub children: Vec<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'raw str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parser: Option<Identifier<'raw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parser_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Value>,
+}
+
+/// The command tree in the layout of the Declare Commands packet, every node
+/// refers to its children and redirect by their index in `nodes`.
+#[derive(Debug, Serialize)]
+pub struct FlatCommands<'raw> {
+    pub root: i32,
+    pub nodes: Vec<FlatNode<'raw>>,
+}
+
+/// How a node changed between two versions.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum CommandChangeKind<'raw> {
+    Added {
+        node: NodeType,
+    },
+    Removed {
+        node: NodeType,
+    },
+    ParserChanged {
+        from: Identifier<'raw>,
+        to: Identifier<'raw>,
+    },
+    PropertiesChanged {
+        from: Option<Value>,
+        to: Option<Value>,
+    },
+}
+
+/// A node that changed between two versions, identified by its path with
+/// arguments written as `<name>`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandChange<'raw> {
+    pub path: String,
+    #[serde(flatten)]
+    pub kind: CommandChangeKind<'raw>,
+}
+
+impl<'raw> Display for CommandChange<'raw> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match &self.kind {
+            CommandChangeKind::Added { node } => write!(f, "added {} node", node),
+            CommandChangeKind::Removed { node } => write!(f, "removed {} node", node),
+            CommandChangeKind::ParserChanged { from, to } => write!(f, "parser changed from {} to {}", from, to),
+            CommandChangeKind::PropertiesChanged { from, to } => {
+                write!(f, "parser properties changed from {} to {}", from.as_ref().unwrap_or(&Value::Null), to.as_ref().unwrap_or(&Value::Null))
+            },
+        }
+    }
+}
+
+/// Lists every node that was added or removed and every argument whose parser
+/// changed between two versions.
+///
+/// Added or removed nodes are reported without their children. A node whose
+/// type changed is reported as removed and added.
+pub fn diff<'raw>(from: &CommandNode<'raw>, to: &CommandNode<'raw>) -> Vec<CommandChange<'raw>> {
+    let mut changes = Vec::new();
+    diff_children(from, to, "", &mut changes);
+    changes
+}
+
+fn diff_children<'raw>(from: &CommandNode<'raw>, to: &CommandNode<'raw>, path: &str, changes: &mut Vec<CommandChange<'raw>>) {
+    let path_of = |name: &str, node: &CommandNode| {
+        let name = match node.kind {
+            NodeKind::Argument { .. } => format!("<{}>", name),
+            _ => name.to_string(),
+        };
+        if path.is_empty() {
+            name
+        } else {
+            format!("{} {}", path, name)
+        }
+    };
+
+    for (&name, old) in &from.children {
+        match to.children.get(name) {
+            Some(new) if new.kind.node_type() == old.kind.node_type() => {
+                let path = path_of(name, new);
+                if let (
+                    NodeKind::Argument {
+                        parser: from,
+                        properties: old,
+                    },
+                    NodeKind::Argument {
+                        parser: to,
+                        properties: new,
+                    },
+                ) = (&old.kind, &new.kind)
+                {
+                    if from != to {
+                        changes.push(CommandChange {
+                            path: path.clone(),
+                            kind: CommandChangeKind::ParserChanged {
+                                from: *from,
+                                to: *to,
+                            },
+                        });
+                    } else if old != new {
+                        changes.push(CommandChange {
+                            path: path.clone(),
+                            kind: CommandChangeKind::PropertiesChanged {
+                                from: old.clone(),
+                                to: new.clone(),
+                            },
+                        });
+                    }
+                }
+                diff_children(old, new, &path, changes);
+            },
+            _ => changes.push(CommandChange {
+                path: path_of(name, old),
+                kind: CommandChangeKind::Removed {
+                    node: old.kind.node_type(),
+                },
+            }),
+        }
+    }
+    for (&name, new) in &to.children {
+        if from
+            .children
+            .get(name)
+            .is_none_or(|old| old.kind.node_type() != new.kind.node_type())
+        {
+            changes.push(CommandChange {
+                path: path_of(name, new),
+                kind: CommandChangeKind::Added {
+                    node: new.kind.node_type(),
+                },
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{diff, CommandChangeKind, CommandError, CommandNode, NodeKind, NodeType, FLAG_ARGUMENT, FLAG_EXECUTABLE, FLAG_LITERAL, FLAG_REDIRECT};
+    use crate::registries::Registry;
+    use crate::util::identifier::Identifier;
+
+    const COMMANDS: &str = r#"{
+        "type": "root",
+        "children": {
+            "gamemode": {
+                "type": "literal",
+                "children": {
+                    "gamemode": {
+                        "type": "argument",
+                        "parser": "minecraft:gamemode",
+                        "executable": true
+                    }
+                }
+            },
+            "tp": { "type": "literal", "redirect": ["teleport"] },
+            "teleport": {
+                "type": "literal",
+                "children": {
+                    "location": {
+                        "type": "argument",
+                        "parser": "minecraft:vec3",
+                        "executable": true
+                    }
+                }
+            },
+            "time": {
+                "type": "literal",
+                "children": {
+                    "time": {
+                        "type": "argument",
+                        "parser": "brigadier:integer",
+                        "properties": { "min": 0 },
+                        "executable": true
+                    }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_parse() {
+        let root: CommandNode = serde_json::from_str(COMMANDS).unwrap();
+        assert_eq!(root.count(), 8);
+        assert_eq!(root.get(&["tp"]).unwrap().redirect, Some(vec!["teleport"]));
+        let argument = root.get(&["gamemode", "gamemode"]).unwrap();
+        assert!(argument.executable);
+        assert_eq!(argument.kind, NodeKind::Argument {
+            parser: Identifier::from_location("gamemode"),
+            properties: None
+        });
+
+        assert!(serde_json::from_str::<CommandNode>(r#"{ "type": "argument" }"#).is_err());
+    }
+
+    #[test]
+    fn test_flatten() {
+        let root: CommandNode = serde_json::from_str(COMMANDS).unwrap();
+        let parsers: Registry = serde_json::from_str(r#"{ "id": 0, "entries": ["brigadier:integer", "minecraft:vec3", "minecraft:gamemode"] }"#).unwrap();
+        let flat = root.flatten(Some(&parsers)).unwrap();
+        assert_eq!(flat.nodes.len(), 8);
+        assert_eq!(flat.nodes[0].children, vec![1, 2, 3, 4]);
+        assert_eq!(flat.nodes[2].name, Some("tp"));
+        assert_eq!(flat.nodes[2].flags, FLAG_LITERAL | FLAG_REDIRECT);
+        assert_eq!(flat.nodes[2].redirect, Some(3));
+        assert_eq!(flat.nodes[5].flags, FLAG_ARGUMENT | FLAG_EXECUTABLE);
+        assert_eq!(flat.nodes[5].parser_id, Some(2));
+        assert_eq!(flat.nodes[1].children, vec![5]);
+
+        let execute: CommandNode = serde_json::from_str(
+            r#"{
+                "type": "root",
+                "children": {
+                    "execute": {
+                        "type": "literal",
+                        "children": {
+                            "run": { "type": "literal" },
+                            "as": {
+                                "type": "literal",
+                                "children": { "targets": { "type": "argument", "parser": "minecraft:entity", "redirect": ["execute"] } }
+                            }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let flat = execute.flatten(None).unwrap();
+        assert_eq!(flat.nodes[2].name, Some("run"));
+        assert_eq!(flat.nodes[2].flags, FLAG_LITERAL | FLAG_REDIRECT);
+        assert_eq!(flat.nodes[2].redirect, Some(0));
+        assert_eq!(flat.nodes[4].flags, FLAG_ARGUMENT | FLAG_REDIRECT);
+        assert_eq!(flat.nodes[4].redirect, Some(1));
+        assert_eq!(flat.nodes[0].redirect, None);
+
+        let parsers: Registry = serde_json::from_str(r#"{ "id": 0, "entries": ["minecraft:vec3"] }"#).unwrap();
+        assert_eq!(root.flatten(Some(&parsers)).unwrap_err(), CommandError::UnknownParser {
+            node: "gamemode gamemode".to_string(),
+            parser: "minecraft:gamemode".to_string()
+        });
+    }
+
+    #[test]
+    fn test_diff() {
+        let from: CommandNode = serde_json::from_str(COMMANDS).unwrap();
+        let to: CommandNode = serde_json::from_str(
+            r#"{
+                "type": "root",
+                "children": {
+                    "gamemode": {
+                        "type": "literal",
+                        "children": { "gamemode": { "type": "argument", "parser": "minecraft:game_mode" } }
+                    },
+                    "teleport": {
+                        "type": "literal",
+                        "children": { "location": { "type": "argument", "parser": "minecraft:vec3" } }
+                    },
+                    "time": {
+                        "type": "literal",
+                        "children": {
+                            "time": { "type": "argument", "parser": "brigadier:integer", "properties": { "min": 1 } }
+                        }
+                    },
+                    "tick": { "type": "literal" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let changes: Vec<(String, CommandChangeKind)> = diff(&from, &to).into_iter().map(|change| (change.path, change.kind)).collect();
+        assert_eq!(changes.len(), 4);
+        assert_eq!(
+            changes[0],
+            ("gamemode <gamemode>".to_string(), CommandChangeKind::ParserChanged {
+                from: Identifier::from_location("gamemode"),
+                to: Identifier::from_location("game_mode")
+            })
+        );
+        assert_eq!(
+            changes[1],
+            ("tp".to_string(), CommandChangeKind::Removed {
+                node: NodeType::Literal
+            })
+        );
+        assert_eq!(
+            changes[2],
+            ("time <time>".to_string(), CommandChangeKind::PropertiesChanged {
+                from: Some(json!({ "min": 0 })),
+                to: Some(json!({ "min": 1 }))
+            })
+        );
+        assert_eq!(
+            changes[3],
+            ("tick".to_string(), CommandChangeKind::Added {
+                node: NodeType::Literal
+            })
+        );
+    }
+}
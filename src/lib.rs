@@ -0,0 +1,133 @@
+//! Data tool for the FalconMC project.
+//!
+//! This library contains everything `mc-data` is built on, so the same code
+//! can be used from build scripts without going through the command line:
+//! - [`check_collisions`] looks for property collisions in raw generator data
+//! - [`suggest_rules`] suggests a rules file resolving those collisions
+//! - [`validate_rules`] looks for mistakes in a rules file
+//! - [`compact`] turns raw generator data into the compact intermediary format
+//! - [`load_intermediary`] loads previously compacted data
+//!
+//! Blockstates in the compact data can be looked up using
+//! [`ModernBlockList::index`]. The compact data itself can be mapped to other
+//! versions using [`blocks::mapping`] or turned into Rust code using
+//! [`codegen`]. The other registries of a version, like items and entity
+//! types, can be compacted using [`registries`] and the packets of newer
+//! versions using [`packets`]. The command tree sent to clients is found in
+//! [`commands`].
+use serde::de::DeserializeSeed;
+use serde::Deserialize;
+use serde_json::Deserializer;
+
+use crate::blocks::intermediary::collisions::CollisionRuleProvider;
+use crate::blocks::intermediary::suggest::{PropertyUsages, RuleSuggester, SuggestedRules, UsageCollector};
+use crate::blocks::intermediary::validate::{RuleIssue, RulesValidator};
+use crate::blocks::raw::de::CompactRuleProvider;
+
+pub mod blocks;
+pub mod codegen;
+pub mod commands;
+pub mod nbt;
+pub mod packets;
+pub mod registries;
+pub mod schematic;
+pub mod util;
+
+pub use blocks::intermediary::collisions::CollisionList;
+pub use blocks::intermediary::data::ModernBlockList;
+pub use blocks::intermediary::lookup::{BlockState, BlockStateIndex};
+pub use blocks::intermediary::rules::ModernPropertyRules;
+pub use blocks::intermediary::MetaData;
+pub use util::blockstate::BlockStateString;
+pub use util::identifier::Identifier;
+
+/// Checks raw data from the Minecraft generators for property collisions,
+/// applying the given rules first.
+pub fn check_collisions<'raw>(data: &'raw str, rules: Option<&ModernPropertyRules<'raw>>) -> serde_json::Result<CollisionList<'raw>> {
+    let mut deserializer = Deserializer::from_str(data);
+    let collisions = CollisionRuleProvider::new(rules).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(collisions)
+}
+
+/// Collects which blocks use the properties involved in the name collisions
+/// found by [`check_collisions`].
+pub fn collect_usages<'raw>(
+    data: &'raw str,
+    rules: Option<&ModernPropertyRules<'raw>>,
+    collisions: &CollisionList<'raw>,
+) -> serde_json::Result<PropertyUsages<'raw>> {
+    let mut deserializer = Deserializer::from_str(data);
+    let usages = UsageCollector::new(rules, collisions).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(usages)
+}
+
+/// Suggests rules resolving the name collisions found by
+/// [`check_collisions`], keeping the given rules intact.
+pub fn suggest_rules<'raw>(
+    data: &'raw str,
+    rules: Option<&ModernPropertyRules<'raw>>,
+    collisions: &CollisionList<'raw>,
+) -> serde_json::Result<SuggestedRules<'raw>> {
+    let usages = collect_usages(data, rules, collisions)?;
+    Ok(RuleSuggester::new(rules, collisions, &usages).suggest())
+}
+
+/// Checks a rules file against raw data from the Minecraft generators,
+/// returning every issue found.
+pub fn validate_rules<'raw>(data: &'raw str, rules: &'raw str) -> serde_json::Result<Vec<RuleIssue<'raw>>> {
+    let rules = load_rules(rules)?;
+    let mut deserializer = Deserializer::from_str(data);
+    let issues = RulesValidator::new(&rules).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(issues)
+}
+
+/// Compacts raw data from the Minecraft generators into the intermediary
+/// format.
+///
+/// This does not check for collisions, use [`check_collisions`] first to make
+/// sure the result is unambiguous.
+pub fn compact<'raw>(
+    data: &'raw str,
+    rules: Option<&ModernPropertyRules<'raw>>,
+    metadata: Option<MetaData<'raw>>,
+) -> serde_json::Result<ModernBlockList<'raw>> {
+    let mut deserializer = Deserializer::from_str(data);
+    let data = CompactRuleProvider::new(rules, metadata).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(data)
+}
+
+/// Loads data in the intermediary format.
+pub fn load_intermediary<'raw>(data: &'raw str) -> serde_json::Result<ModernBlockList<'raw>> {
+    let mut deserializer = Deserializer::from_str(data);
+    let data = ModernBlockList::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(data)
+}
+
+/// Loads a rules file to be used by [`check_collisions`] and [`compact`].
+pub fn load_rules<'raw>(data: &'raw str) -> serde_json::Result<ModernPropertyRules<'raw>> {
+    let mut deserializer = Deserializer::from_str(data);
+    let rules = ModernPropertyRules::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    const RAW: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/blocks.json"));
+
+    #[test]
+    fn test_trailing_data() {
+        assert!(crate::load_rules(r#"{ "facing": ["north", "south"] }"#).is_ok());
+        assert!(crate::load_rules(r#"{ "facing": ["north", "south"] } }"#).is_err());
+
+        let data = serde_json::to_string(&crate::compact(RAW, None, None).unwrap()).unwrap();
+        assert!(crate::load_intermediary(&data).is_ok());
+        assert!(crate::load_intermediary(&format!("{} []", data)).is_err());
+        assert!(crate::check_collisions(&format!("{}garbage", RAW), None).is_err());
+    }
+}
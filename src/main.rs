@@ -1,10 +1,11 @@
 use anyhow::Context;
 use clap::{Parser, Subcommand};
-use cmd::{InfoCommand, IntermediaryCommand};
+use cmd::{
+    CodegenCommand, CommandsCommand, CompareCommand, ExpandCommand, InfoCommand, IntermediaryCommand, LegacyCommand, MatrixCommand, PacketsCommand,
+    QueryCommand, RegistriesCommand, RegistryMapCommand, RulesCommand, SchematicCommand,
+};
 
-pub mod blocks;
-pub mod cmd;
-pub mod util;
+mod cmd;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -17,13 +18,44 @@ pub struct Cli {
 pub enum SubCommands {
     Intermediary(IntermediaryCommand),
     Info(InfoCommand),
+    Compare(CompareCommand),
+    Codegen(CodegenCommand),
+    Query(QueryCommand),
+    Expand(ExpandCommand),
+    Schematic(SchematicCommand),
+    Rules(RulesCommand),
+    Matrix(MatrixCommand),
+    Legacy(LegacyCommand),
+    Registries(RegistriesCommand),
+    RegistryMap(RegistryMapCommand),
+    Packets(PacketsCommand),
+    Commands(CommandsCommand),
 }
 
-fn main() -> anyhow::Result<()> {
+fn main() {
     let cli = Cli::parse();
 
-    match cli.command {
+    let result = match cli.command {
         SubCommands::Intermediary(cmd) => cmd.generate_intermediate().context("Error while generating data"),
         SubCommands::Info(cmd) => cmd.display_info().context("Error while displaying info"),
+        SubCommands::Compare(cmd) => cmd.compare().context("Error while comparing data"),
+        SubCommands::Codegen(cmd) => cmd.generate_code().context("Error while generating code"),
+        SubCommands::Query(cmd) => cmd.query().context("Error while querying data"),
+        SubCommands::Expand(cmd) => cmd.expand().context("Error while expanding data"),
+        SubCommands::Schematic(cmd) => cmd.execute().context("Error while processing schematic"),
+        SubCommands::Rules(cmd) => cmd.execute().context("Error while processing rules"),
+        SubCommands::Matrix(cmd) => cmd.generate_matrix().context("Error while generating matrix"),
+        SubCommands::Legacy(cmd) => cmd.map_legacy().context("Error while mapping legacy blocks"),
+        SubCommands::Registries(cmd) => cmd.compact_registries().context("Error while compacting registries"),
+        SubCommands::RegistryMap(cmd) => cmd.map_registries().context("Error while mapping registries"),
+        SubCommands::Packets(cmd) => cmd.execute().context("Error while processing packets"),
+        SubCommands::Commands(cmd) => cmd.execute().context("Error while processing commands"),
+    };
+
+    if let Err(error) = result {
+        if !cmd::is_reported(&error) {
+            eprintln!("Error: {:?}", error);
+        }
+        std::process::exit(cmd::exit_code(&error));
     }
 }
@@ -0,0 +1,92 @@
+use std::fmt::Formatter;
+
+use serde::de::{SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{BYTE_ARRAY, INT_ARRAY, LONG_ARRAY};
+
+/// Serializes a slice of bytes using `serialize_bytes`.
+struct Bytes<'a>(&'a [u8]);
+
+/// Serializes the big-endian bytes of an array as the array tag named by
+/// `marker`.
+pub(super) fn serialize_array<S: Serializer>(serializer: S, marker: &'static str, bytes: &[u8]) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(marker, &Bytes(bytes))
+}
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+macro_rules! array {
+    ($(#[$attr:meta])* $name:ident, $element:ty, $marker:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        pub struct $name(pub Vec<$element>);
+
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let bytes: Vec<u8> = self.0.iter().flat_map(|value| value.to_be_bytes()).collect();
+                serialize_array(serializer, $marker, &bytes)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct ArrayVisitor;
+
+                impl<'de> Visitor<'de> for ArrayVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result { formatter.write_str(concat!("an nbt ", stringify!($element), " array")) }
+
+                    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                    where
+                        D: Deserializer<'de>,
+                    {
+                        Vec::deserialize(deserializer).map($name)
+                    }
+
+                    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: SeqAccess<'de>,
+                    {
+                        Vec::deserialize(serde::de::value::SeqAccessDeserializer::new(seq)).map($name)
+                    }
+                }
+
+                deserializer.deserialize_newtype_struct($marker, ArrayVisitor)
+            }
+        }
+    };
+}
+
+array!(
+    /// A byte array tag, a plain `Vec<i8>` would be serialized as a list.
+    ByteArray,
+    i8,
+    BYTE_ARRAY
+);
+array!(
+    /// An int array tag, a plain `Vec<i32>` would be serialized as a list.
+    IntArray,
+    i32,
+    INT_ARRAY
+);
+array!(
+    /// A long array tag, a plain `Vec<i64>` would be serialized as a list.
+    LongArray,
+    i64,
+    LONG_ARRAY
+);
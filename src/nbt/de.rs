@@ -0,0 +1,300 @@
+use std::borrow::Cow;
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::{mutf8, NbtError, TAG};
+
+/// Deserializes NBT data borrowing from the input where possible.
+///
+/// Strings are borrowed as `&'de str` unless their modified UTF-8 differs
+/// from regular UTF-8, and byte arrays can be borrowed as `&'de [u8]`.
+/// Unsigned integers are read from the signed tag of the same width, keeping
+/// their bits.
+pub struct Deserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer for uncompressed data.
+    pub fn from_slice(input: &'de [u8]) -> Self { Self { input } }
+
+    /// Returns the data that hasn't been read yet.
+    pub fn remaining(&self) -> &'de [u8] { self.input }
+
+    /// Deserializes a named root tag together with its name.
+    pub fn deserialize_root<T: de::Deserialize<'de>>(&mut self) -> Result<(Cow<'de, str>, T), NbtError> {
+        let id = self.read_u8()?;
+        let name = self.read_str()?;
+        Ok((name, T::deserialize(Value { de: self, id })?))
+    }
+
+    /// Deserializes a named root tag, the name of the root is ignored.
+    pub fn deserialize_named<T: de::Deserialize<'de>>(&mut self) -> Result<T, NbtError> { self.deserialize_root().map(|(_, value)| value) }
+
+    /// Deserializes a nameless root tag as used by the network protocol since
+    /// 1.20.2.
+    pub fn deserialize_nameless<T: de::Deserialize<'de>>(&mut self) -> Result<T, NbtError> {
+        let id = self.read_u8()?;
+        T::deserialize(Value { de: self, id })
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'de [u8], NbtError> {
+        if self.input.len() < len {
+            return Err(NbtError::Eof);
+        }
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], NbtError> { Ok(self.read_bytes(N)?.try_into().unwrap()) }
+
+    fn read_u8(&mut self) -> Result<u8, NbtError> { Ok(self.read_array::<1>()?[0]) }
+
+    fn read_length(&mut self) -> Result<usize, NbtError> {
+        let len = i32::from_be_bytes(self.read_array()?);
+        usize::try_from(len).map_err(|_| NbtError::NegativeLength(len))
+    }
+
+    fn read_str(&mut self) -> Result<Cow<'de, str>, NbtError> {
+        let len = u16::from_be_bytes(self.read_array()?);
+        mutf8::decode(self.read_bytes(len as usize)?).ok_or(NbtError::InvalidString)
+    }
+
+    /// Skips the payload of a tag with the given type id.
+    fn skip(&mut self, id: u8) -> Result<(), NbtError> {
+        match id {
+            1..=6 => {
+                self.read_bytes([1, 2, 4, 8, 4, 8][id as usize - 1])?;
+            },
+            7 => {
+                let len = self.read_length()?;
+                self.read_bytes(len)?;
+            },
+            8 => {
+                self.read_str()?;
+            },
+            9 => {
+                let element = self.read_u8()?;
+                for _ in 0..self.read_length()? {
+                    self.skip(element)?;
+                }
+            },
+            10 => loop {
+                let id = self.read_u8()?;
+                if id == 0 {
+                    break;
+                }
+                self.read_str()?;
+                self.skip(id)?;
+            },
+            11 => {
+                let len = self.read_length()?;
+                self.read_bytes(len * 4)?;
+            },
+            12 => {
+                let len = self.read_length()?;
+                self.read_bytes(len * 8)?;
+            },
+            id => return Err(NbtError::InvalidTagType(id)),
+        }
+        Ok(())
+    }
+}
+
+/// The payload of a single tag with a known type id.
+struct Value<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    id: u8,
+}
+
+impl<'a, 'de> de::Deserializer<'de> for Value<'a, 'de> {
+    type Error = NbtError;
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u128 f32 f64 char str string
+        byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let de = self.de;
+        match self.id {
+            1 => visitor.visit_i8(de.read_u8()? as i8),
+            2 => visitor.visit_i16(i16::from_be_bytes(de.read_array()?)),
+            3 => visitor.visit_i32(i32::from_be_bytes(de.read_array()?)),
+            4 => visitor.visit_i64(i64::from_be_bytes(de.read_array()?)),
+            5 => visitor.visit_f32(f32::from_be_bytes(de.read_array()?)),
+            6 => visitor.visit_f64(f64::from_be_bytes(de.read_array()?)),
+            8 => match de.read_str()? {
+                Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+                Cow::Owned(value) => visitor.visit_string(value),
+            },
+            7 | 11 | 12 => {
+                let id = match self.id {
+                    7 => 1,
+                    11 => 3,
+                    _ => 4,
+                };
+                let remaining = de.read_length()?;
+                visitor.visit_seq(List { de, id, remaining })
+            },
+            9 => {
+                let id = de.read_u8()?;
+                let remaining = de.read_length()?;
+                visitor.visit_seq(List { de, id, remaining })
+            },
+            10 => visitor.visit_map(Compound { de, id: 0 }),
+            id => Err(NbtError::InvalidTagType(id)),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.id {
+            1 => visitor.visit_bool(self.de.read_u8()? != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.id {
+            1 => visitor.visit_u8(self.de.read_u8()?),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.id {
+            2 => visitor.visit_u16(u16::from_be_bytes(self.de.read_array()?)),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.id {
+            3 => visitor.visit_u32(u32::from_be_bytes(self.de.read_array()?)),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.id {
+            4 => visitor.visit_u64(u64::from_be_bytes(self.de.read_array()?)),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.id {
+            7 => {
+                let len = self.de.read_length()?;
+                visitor.visit_borrowed_bytes(self.de.read_bytes(len)?)
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> { visitor.visit_some(self) }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        match name {
+            TAG => visitor.visit_enum(self),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        match self.id {
+            8 => match self.de.read_str()? {
+                Cow::Borrowed(variant) => visitor.visit_enum(BorrowedStrDeserializer::new(variant)),
+                Cow::Owned(variant) => visitor.visit_enum(variant.into_deserializer()),
+            },
+            _ => Err(NbtError::UnsupportedType("enum")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.de.skip(self.id)?;
+        visitor.visit_unit()
+    }
+}
+
+/// Exposes the type id of a tag as enum variant, so [`Tag`](super::Tag) can
+/// tell arrays and lists apart.
+impl<'a, 'de> de::EnumAccess<'de> for Value<'a, 'de> {
+    type Error = NbtError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let id = seed.deserialize(IntoDeserializer::<NbtError>::into_deserializer(self.id))?;
+        Ok((id, self))
+    }
+}
+
+impl<'a, 'de> de::VariantAccess<'de> for Value<'a, 'de> {
+    type Error = NbtError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> { Err(NbtError::UnsupportedType("unit variant")) }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Self::Error> { seed.deserialize(self) }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error> { Err(NbtError::UnsupportedType("tuple variant")) }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(NbtError::UnsupportedType("struct variant"))
+    }
+}
+
+/// A list or array, arrays use the type id of their elements.
+struct List<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    id: u8,
+    remaining: usize,
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for List<'a, 'de> {
+    type Error = NbtError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(Value {
+            de: &mut *self.de,
+            id: self.id,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> { Some(self.remaining) }
+}
+
+/// A compound, `id` is the type id of the next value.
+struct Compound<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    id: u8,
+}
+
+impl<'a, 'de> de::MapAccess<'de> for Compound<'a, 'de> {
+    type Error = NbtError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        self.id = self.de.read_u8()?;
+        if self.id == 0 {
+            return Ok(None);
+        }
+        match self.de.read_str()? {
+            Cow::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)).map(Some),
+            Cow::Owned(key) => seed.deserialize(key.into_deserializer()).map(Some),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        seed.deserialize(Value {
+            de: &mut *self.de,
+            id: self.id,
+        })
+    }
+}
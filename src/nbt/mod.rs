@@ -0,0 +1,324 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub use self::array::{ByteArray, IntArray, LongArray};
+pub use self::de::Deserializer;
+pub use self::ser::Serializer;
+pub use self::tag::Tag;
+
+pub mod array;
+pub mod de;
+mod mutf8;
+pub mod ser;
+pub mod tag;
+
+/// Marker names used to serialize arrays instead of lists.
+const BYTE_ARRAY: &str = "__nbt_byte_array";
+const INT_ARRAY: &str = "__nbt_int_array";
+const LONG_ARRAY: &str = "__nbt_long_array";
+/// Marker name used to deserialize a [`Tag`] together with its type id.
+const TAG: &str = "__nbt_tag";
+
+#[derive(Debug, Error)]
+pub enum NbtError {
+    #[error("I/O error while reading or writing nbt")]
+    Io(#[from] io::Error),
+    #[error("Unexpected end of nbt data")]
+    Eof,
+    #[error("Invalid tag type {0}")]
+    InvalidTagType(u8),
+    #[error("Expected a compound as root tag, found tag type {0}")]
+    InvalidRoot(u8),
+    #[error("Negative length {0}")]
+    NegativeLength(i32),
+    #[error("Invalid string data")]
+    InvalidString,
+    #[error("String of length {0} is too long")]
+    StringTooLong(usize),
+    #[error("List elements have different tag types ({0} and {1})")]
+    MixedList(u8, u8),
+    #[error("Compound keys must be strings")]
+    KeyMustBeString,
+    #[error("Unsupported type: {0}")]
+    UnsupportedType(&'static str),
+    #[error("{0}")]
+    Message(String),
+}
+
+impl serde::de::Error for NbtError {
+    fn custom<T: Display>(msg: T) -> Self { NbtError::Message(msg.to_string()) }
+}
+
+impl serde::ser::Error for NbtError {
+    fn custom<T: Display>(msg: T) -> Self { NbtError::Message(msg.to_string()) }
+}
+
+/// The compression applied to nbt data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zlib,
+}
+
+impl Compression {
+    /// Detects the compression of the given data based on its first bytes.
+    pub fn detect(data: &[u8]) -> Self {
+        match data {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [0x78, 0x01 | 0x5e | 0x9c | 0xda, ..] => Compression::Zlib,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Decompresses nbt data, uncompressed data is borrowed as is.
+pub fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>, NbtError> {
+    let mut decompressed = Vec::new();
+    match Compression::detect(data) {
+        Compression::None => return Ok(Cow::Borrowed(data)),
+        Compression::Gzip => GzDecoder::new(data).read_to_end(&mut decompressed)?,
+        Compression::Zlib => ZlibDecoder::new(data).read_to_end(&mut decompressed)?,
+    };
+    Ok(Cow::Owned(decompressed))
+}
+
+/// Compresses data written to the inner writer.
+enum Encoder<W: Write> {
+    None(W),
+    Gzip(GzEncoder<W>),
+    Zlib(ZlibEncoder<W>),
+}
+
+impl<W: Write> Encoder<W> {
+    fn new(writer: W, compression: Compression) -> Self {
+        match compression {
+            Compression::None => Encoder::None(writer),
+            Compression::Gzip => Encoder::Gzip(GzEncoder::new(writer, flate2::Compression::default())),
+            Compression::Zlib => Encoder::Zlib(ZlibEncoder::new(writer, flate2::Compression::default())),
+        }
+    }
+
+    /// Writes any remaining compressed data and flushes the inner writer.
+    fn finish(self) -> io::Result<()> {
+        let mut writer = match self {
+            Encoder::None(writer) => writer,
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            Encoder::Zlib(encoder) => encoder.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::None(writer) => writer.write(buf),
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            Encoder::Zlib(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::None(writer) => writer.flush(),
+            Encoder::Gzip(encoder) => encoder.flush(),
+            Encoder::Zlib(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Deserializes a value from an uncompressed named root tag.
+///
+/// Compressed data should be passed through [`decompress`] first, the
+/// resulting value can then borrow from the decompressed data.
+pub fn from_slice<'de, T: Deserialize<'de>>(data: &'de [u8]) -> Result<T, NbtError> { Deserializer::from_slice(data).deserialize_named() }
+
+/// Deserializes a value from an uncompressed nameless root tag, as used by
+/// the network protocol since 1.20.2.
+pub fn from_slice_network<'de, T: Deserialize<'de>>(data: &'de [u8]) -> Result<T, NbtError> { Deserializer::from_slice(data).deserialize_nameless() }
+
+/// Serializes a value as a named root tag using the given compression.
+pub fn to_writer<W: Write, T: Serialize + ?Sized>(writer: W, name: &str, value: &T, compression: Compression) -> Result<(), NbtError> {
+    let mut serializer = Serializer::new(Encoder::new(writer, compression));
+    serializer.serialize_named(name, value)?;
+    Ok(serializer.into_inner().finish()?)
+}
+
+/// Serializes a value as an uncompressed nameless root tag, as used by the
+/// network protocol since 1.20.2.
+pub fn to_vec_network<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, NbtError> {
+    let mut serializer = Serializer::new(Vec::new());
+    serializer.serialize_nameless(value)?;
+    Ok(serializer.into_inner())
+}
+
+/// Reads a named root compound, decompressing the data first if needed.
+pub fn read(data: &[u8]) -> Result<(String, Tag), NbtError> {
+    let data = decompress(data)?;
+    let (name, tag): (_, Tag) = Deserializer::from_slice(&data).deserialize_root()?;
+    match tag.id() {
+        10 => Ok((name.into_owned(), tag)),
+        id => Err(NbtError::InvalidRoot(id)),
+    }
+}
+
+/// Writes a named root tag using the given compression.
+pub fn write<W: Write>(writer: W, name: &str, tag: &Tag, compression: Compression) -> Result<(), NbtError> { to_writer(writer, name, tag, compression) }
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Schematic<'a> {
+        version: i32,
+        name: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
+        offset: IntArray,
+        block_data: ByteArray,
+        palette: Vec<Entry<'a>>,
+        kind: Kind,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entry<'a> {
+        #[serde(borrow)]
+        state: &'a str,
+        waterlogged: bool,
+        heights: LongArray,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Sponge,
+    }
+
+    fn schematic() -> Schematic<'static> {
+        Schematic {
+            version: 2,
+            name: "test",
+            author: None,
+            offset: IntArray(vec![1, -2, 3]),
+            block_data: ByteArray(vec![0, 1, -1]),
+            palette: vec![Entry {
+                state: "minecraft:stone",
+                waterlogged: true,
+                heights: LongArray(vec![i64::MAX]),
+            }],
+            kind: Kind::Sponge,
+        }
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        for compression in [Compression::None, Compression::Gzip, Compression::Zlib] {
+            let mut data = Vec::new();
+            to_writer(&mut data, "Schematic", &schematic(), compression).unwrap();
+            assert_eq!(Compression::detect(&data), compression);
+
+            let data = decompress(&data).unwrap();
+            let result: Schematic = from_slice(&data).unwrap();
+            assert_eq!(result, schematic());
+        }
+    }
+
+    #[test]
+    fn test_serde_tag_interop() {
+        let mut data = Vec::new();
+        to_writer(&mut data, "Schematic", &schematic(), Compression::None).unwrap();
+
+        let (name, tag) = read(&data).unwrap();
+        assert_eq!(name, "Schematic");
+        assert_eq!(tag.get("Author"), None);
+        assert_eq!(tag.get("Offset"), Some(&Tag::IntArray(vec![1, -2, 3])));
+        assert_eq!(tag.get("Kind"), Some(&Tag::String("Sponge".to_string())));
+        assert_eq!(tag.get("Palette").and_then(|palette| palette.get("state")), None);
+
+        let mut written = Vec::new();
+        write(&mut written, &name, &tag, Compression::None).unwrap();
+        assert_eq!(written, data);
+    }
+
+    #[test]
+    fn test_network_nbt() {
+        let data = to_vec_network(&schematic()).unwrap();
+        assert_eq!(&data[..2], &[10, 3]);
+        let result: Schematic = from_slice_network(&data).unwrap();
+        assert_eq!(result, schematic());
+        assert_eq!(from_slice_network::<Tag>(&data).unwrap().get("Version"), Some(&Tag::Int(2)));
+    }
+
+    #[test]
+    fn test_unsigned_round_trip() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Unsigned {
+            byte: u8,
+            short: u16,
+            int: u32,
+            long: u64,
+            bytes: Vec<u8>,
+        }
+
+        let value = Unsigned {
+            byte: 200,
+            short: u16::MAX,
+            int: 3_000_000_000,
+            long: u64::MAX,
+            bytes: vec![0, 128, 255],
+        };
+        let data = to_vec_network(&value).unwrap();
+        let tag = from_slice_network::<Tag>(&data).unwrap();
+        assert_eq!(tag.get("byte"), Some(&Tag::Byte(-56)));
+        assert_eq!(tag.get("short"), Some(&Tag::Short(-1)));
+        assert_eq!(tag.get("int"), Some(&Tag::Int(-1_294_967_296)));
+        assert_eq!(tag.get("long"), Some(&Tag::Long(-1)));
+        assert_eq!(from_slice_network::<Unsigned>(&data).unwrap(), value);
+    }
+
+    #[test]
+    fn test_modified_utf8_strings() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Text {
+            text: String,
+        }
+
+        let value = Text {
+            text: "null \0 and \u{1F600}".to_string(),
+        };
+        let data = to_vec_network(&value).unwrap();
+        assert!(data.windows(2).any(|bytes| bytes == [0xC0, 0x80]));
+        assert!(!data.contains(&0xF0));
+        assert_eq!(from_slice_network::<Text>(&data).unwrap(), value);
+        assert_eq!(from_slice_network::<Tag>(&data).unwrap().get("text").and_then(Tag::as_str), Some(value.text.as_str()));
+    }
+
+    #[test]
+    fn test_borrowed_bytes() {
+        #[derive(Serialize)]
+        struct Owned {
+            data: ByteArray,
+        }
+        #[derive(Deserialize)]
+        struct Borrowed<'a> {
+            data: &'a [u8],
+        }
+
+        let data = to_vec_network(&Owned {
+            data: ByteArray(vec![1, 2, 3]),
+        })
+        .unwrap();
+        let result: Borrowed = from_slice_network(&data).unwrap();
+        assert_eq!(result.data, &[1, 2, 3]);
+    }
+}
@@ -0,0 +1,69 @@
+//! Java's modified UTF-8, used for every string in NBT data.
+//!
+//! It only differs from regular UTF-8 in two ways: null characters are
+//! written as the two bytes `C0 80`, and characters outside of the basic
+//! multilingual plane are written as a surrogate pair of two three-byte
+//! sequences.
+use std::borrow::Cow;
+
+/// Decodes modified UTF-8, borrowing the data if it is valid UTF-8 as is.
+pub fn decode(bytes: &[u8]) -> Option<Cow<'_, str>> {
+    if let Ok(value) = std::str::from_utf8(bytes) {
+        return Some(Cow::Borrowed(value));
+    }
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter().copied();
+    while let Some(byte) = bytes.next() {
+        let mut continuation = || bytes.next().filter(|byte| byte & 0xC0 == 0x80).map(|byte| (byte & 0x3F) as u16);
+        let unit = match byte {
+            0x00..=0x7F => byte as u16,
+            0xC0..=0xDF => ((byte & 0x1F) as u16) << 6 | continuation()?,
+            0xE0..=0xEF => ((byte & 0x0F) as u16) << 12 | continuation()? << 6 | continuation()?,
+            _ => return None,
+        };
+        units.push(unit);
+    }
+    String::from_utf16(&units).ok().map(Cow::Owned)
+}
+
+/// Encodes a string as modified UTF-8, borrowing the data if it doesn't
+/// contain null characters or characters outside of the basic multilingual
+/// plane.
+pub fn encode(value: &str) -> Cow<'_, [u8]> {
+    if !value.bytes().any(|byte| byte == 0 || byte >= 0xF0) {
+        return Cow::Borrowed(value.as_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(value.len() + 2);
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7F => bytes.push(unit as u8),
+            0x00 | 0x80..=0x7FF => bytes.extend([0xC0 | (unit >> 6) as u8, 0x80 | (unit & 0x3F) as u8]),
+            _ => bytes.extend([0xE0 | (unit >> 12) as u8, 0x80 | (unit >> 6 & 0x3F) as u8, 0x80 | (unit & 0x3F) as u8]),
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{decode, encode};
+
+    #[test]
+    fn test_modified_utf8() {
+        assert!(matches!(encode("minecraft:stone"), Cow::Borrowed(_)));
+        assert!(matches!(decode("\u{e9}t\u{e9}".as_bytes()), Some(Cow::Borrowed("\u{e9}t\u{e9}"))));
+
+        assert_eq!(encode("a\0b").as_ref(), &[b'a', 0xC0, 0x80, b'b']);
+        assert_eq!(encode("\u{1F600}").as_ref(), &[0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]);
+        for value in ["a\0b", "\u{1F600} and \u{e9}", "\0"] {
+            assert_eq!(decode(&encode(value)).as_deref(), Some(value));
+        }
+
+        assert_eq!(decode(&[0xED, 0xA0, 0xBD]), None);
+        assert_eq!(decode(&[0xC0]), None);
+    }
+}
@@ -0,0 +1,470 @@
+use std::io::Write;
+
+use serde::ser::{self, Impossible, Serialize};
+
+use super::{mutf8, NbtError, BYTE_ARRAY, INT_ARRAY, LONG_ARRAY};
+
+/// Serializes values as NBT data.
+///
+/// Structs and maps become compounds, sequences become lists and `None`
+/// values are left out entirely. Byte, int and long arrays can be written
+/// using the [`ByteArray`](super::ByteArray), [`IntArray`](super::IntArray)
+/// and [`LongArray`](super::LongArray) wrappers.
+///
+/// Unsigned integers are written as the signed tag of the same width, keeping
+/// their bits: `u8` becomes a byte, `u16` a short, `u32` an int and `u64` a
+/// long. Values above the signed maximum appear negative to Java, the
+/// [`Deserializer`](super::Deserializer) reads them back unchanged.
+pub struct Serializer<W> {
+    writer: W,
+}
+
+impl<W: Write> Serializer<W> {
+    pub fn new(writer: W) -> Self { Self { writer } }
+
+    pub fn into_inner(self) -> W { self.writer }
+
+    /// Serializes a value as a named root tag.
+    pub fn serialize_named<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), NbtError> {
+        value.serialize(Value::new(&mut self.writer, Header::Named(name)))
+    }
+
+    /// Serializes a value as a nameless root tag as used by the network
+    /// protocol since 1.20.2.
+    pub fn serialize_nameless<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        value.serialize(Value::new(&mut self.writer, Header::Nameless))
+    }
+}
+
+/// What needs to be written before the payload of a tag.
+enum Header<'h> {
+    /// The type id and name of the tag
+    Named(&'h str),
+    /// Only the type id of the tag
+    Nameless,
+    /// Nothing, but the type id has to match the other elements of the list
+    Element(&'h mut Option<u8>),
+}
+
+/// The serializer of a single tag.
+struct Value<'a, 'h, W> {
+    writer: &'a mut W,
+    header: Header<'h>,
+    /// The type id of the array being serialized, if any
+    array: Option<u8>,
+}
+
+impl<'a, 'h, W: Write> Value<'a, 'h, W> {
+    fn new(writer: &'a mut W, header: Header<'h>) -> Self {
+        Self {
+            writer,
+            header,
+            array: None,
+        }
+    }
+
+    /// Writes the header for a tag of the given type id.
+    fn start(&mut self, id: u8) -> Result<(), NbtError> {
+        match &mut self.header {
+            Header::Named(name) => {
+                self.writer.write_all(&[id])?;
+                write_str(self.writer, name)?;
+            },
+            Header::Nameless => self.writer.write_all(&[id])?,
+            Header::Element(expected) => match expected {
+                Some(expected) if *expected != id => return Err(NbtError::MixedList(*expected, id)),
+                Some(_) => {},
+                None => **expected = Some(id),
+            },
+        }
+        Ok(())
+    }
+
+    fn write(mut self, id: u8, payload: &[u8]) -> Result<(), NbtError> {
+        self.start(id)?;
+        self.writer.write_all(payload)?;
+        Ok(())
+    }
+
+    /// Starts a compound holding a single entry named after an enum variant.
+    fn start_variant(mut self, variant: &str, id: u8) -> Result<&'a mut W, NbtError> {
+        self.start(10)?;
+        self.writer.write_all(&[id])?;
+        write_str(self.writer, variant)?;
+        Ok(self.writer)
+    }
+}
+
+fn write_str<W: Write>(writer: &mut W, value: &str) -> Result<(), NbtError> {
+    let bytes = mutf8::encode(value);
+    let len = u16::try_from(bytes.len()).map_err(|_| NbtError::StringTooLong(bytes.len()))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+impl<'a, 'h, W: Write> ser::Serializer for Value<'a, 'h, W> {
+    type Error = NbtError;
+    type Ok = ();
+    type SerializeMap = Compound<'a, W>;
+    type SerializeSeq = List<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+    type SerializeTuple = List<'a, W>;
+    type SerializeTupleStruct = List<'a, W>;
+    type SerializeTupleVariant = List<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> { self.write(1, &[v as u8]) }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> { self.write(1, &v.to_be_bytes()) }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> { self.write(2, &v.to_be_bytes()) }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> { self.write(3, &v.to_be_bytes()) }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> { self.write(4, &v.to_be_bytes()) }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { self.write(1, &[v]) }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> { self.write(2, &v.to_be_bytes()) }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> { self.write(3, &v.to_be_bytes()) }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> { self.write(4, &v.to_be_bytes()) }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> { self.write(5, &v.to_be_bytes()) }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> { self.write(6, &v.to_be_bytes()) }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> { self.serialize_str(v.encode_utf8(&mut [0; 4])) }
+
+    fn serialize_str(mut self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.start(8)?;
+        write_str(self.writer, v)
+    }
+
+    fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        let (id, size) = match self.array {
+            Some(3) => (11, 4),
+            Some(4) => (12, 8),
+            _ => (7, 1),
+        };
+        self.start(id)?;
+        self.writer.write_all(&((v.len() / size) as i32).to_be_bytes())?;
+        self.writer.write_all(v)?;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        match self.header {
+            Header::Element(_) => Err(NbtError::UnsupportedType("none in a list")),
+            _ => Ok(()),
+        }
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> { value.serialize(self) }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { Err(NbtError::UnsupportedType("unit")) }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> { self.write(10, &[0]) }
+
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(mut self, name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.array = match name {
+            BYTE_ARRAY => Some(1),
+            INT_ARRAY => Some(3),
+            LONG_ARRAY => Some(4),
+            _ => None,
+        };
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.start(10)?;
+        value.serialize(Value::new(self.writer, Header::Named(variant)))?;
+        self.writer.write_all(&[0])?;
+        Ok(())
+    }
+
+    fn serialize_seq(mut self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.start(9)?;
+        Ok(List::new(self.writer, false))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> { self.serialize_seq(Some(len)) }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> { self.serialize_seq(Some(len)) }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(List::new(self.start_variant(variant, 9)?, true))
+    }
+
+    fn serialize_map(mut self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.start(10)?;
+        Ok(Compound::new(self.writer, false))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> { self.serialize_map(Some(len)) }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(Compound::new(self.start_variant(variant, 10)?, true))
+    }
+}
+
+/// Serializes a list, the elements are buffered as the element type and
+/// length have to be written first.
+struct List<'a, W> {
+    writer: &'a mut W,
+    buffer: Vec<u8>,
+    id: Option<u8>,
+    len: usize,
+    /// Whether this list is wrapped in a compound for an enum variant
+    variant: bool,
+}
+
+impl<'a, W: Write> List<'a, W> {
+    fn new(writer: &'a mut W, variant: bool) -> Self {
+        Self {
+            writer,
+            buffer: Vec::new(),
+            id: None,
+            len: 0,
+            variant,
+        }
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        self.len += 1;
+        value.serialize(Value::new(&mut self.buffer, Header::Element(&mut self.id)))
+    }
+
+    fn finish(self) -> Result<(), NbtError> {
+        self.writer.write_all(&[self.id.unwrap_or(0)])?;
+        self.writer.write_all(&(self.len as i32).to_be_bytes())?;
+        self.writer.write_all(&self.buffer)?;
+        if self.variant {
+            self.writer.write_all(&[0])?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> ser::SerializeSeq for List<'a, W> {
+    type Error = NbtError;
+    type Ok = ();
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> { self.element(value) }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
+}
+
+impl<'a, W: Write> ser::SerializeTuple for List<'a, W> {
+    type Error = NbtError;
+    type Ok = ();
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> { self.element(value) }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
+}
+
+impl<'a, W: Write> ser::SerializeTupleStruct for List<'a, W> {
+    type Error = NbtError;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> { self.element(value) }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
+}
+
+impl<'a, W: Write> ser::SerializeTupleVariant for List<'a, W> {
+    type Error = NbtError;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> { self.element(value) }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
+}
+
+/// Serializes a compound, every entry is written as a named tag.
+struct Compound<'a, W> {
+    writer: &'a mut W,
+    key: Option<String>,
+    /// Whether this compound is wrapped in another compound for an enum
+    /// variant
+    variant: bool,
+}
+
+impl<'a, W: Write> Compound<'a, W> {
+    fn new(writer: &'a mut W, variant: bool) -> Self {
+        Self {
+            writer,
+            key: None,
+            variant,
+        }
+    }
+
+    fn finish(self) -> Result<(), NbtError> {
+        self.writer.write_all(&[0])?;
+        if self.variant {
+            self.writer.write_all(&[0])?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> ser::SerializeMap for Compound<'a, W> {
+    type Error = NbtError;
+    type Ok = ();
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self.key.take().ok_or(NbtError::KeyMustBeString)?;
+        value.serialize(Value::new(self.writer, Header::Named(&key)))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
+}
+
+impl<'a, W: Write> ser::SerializeStruct for Compound<'a, W> {
+    type Error = NbtError;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+        value.serialize(Value::new(self.writer, Header::Named(key)))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
+}
+
+impl<'a, W: Write> ser::SerializeStructVariant for Compound<'a, W> {
+    type Error = NbtError;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+        value.serialize(Value::new(self.writer, Header::Named(key)))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> { self.finish() }
+}
+
+/// Only accepts strings, as compound keys can't be anything else.
+struct KeySerializer;
+
+impl ser::Serializer for KeySerializer {
+    type Error = NbtError;
+    type Ok = String;
+    type SerializeMap = Impossible<String, NbtError>;
+    type SerializeSeq = Impossible<String, NbtError>;
+    type SerializeStruct = Impossible<String, NbtError>;
+    type SerializeStructVariant = Impossible<String, NbtError>;
+    type SerializeTuple = Impossible<String, NbtError>;
+    type SerializeTupleStruct = Impossible<String, NbtError>;
+    type SerializeTupleVariant = Impossible<String, NbtError>;
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> { Ok(v.to_string()) }
+
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> { value.serialize(self) }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(NbtError::KeyMustBeString)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(NbtError::KeyMustBeString)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error> { Err(NbtError::KeyMustBeString) }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(NbtError::KeyMustBeString)
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt::Formatter;
+
+use ahash::RandomState;
+use hashlink::LinkedHashMap;
+use serde::de::{EnumAccess, VariantAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::array::{serialize_array, ByteArray, IntArray, LongArray};
+use super::{NbtError, BYTE_ARRAY, INT_ARRAY, LONG_ARRAY, TAG};
+
+/// A single NBT tag, for data that has to be kept as it is.
+///
+/// Tags are read and written through the [`Serializer`](super::Serializer)
+/// and [`Deserializer`](super::Deserializer) like any other value. They can
+/// only be deserialized from NBT data, other formats don't know about the
+/// type of a tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(LinkedHashMap<String, Tag, RandomState>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    pub const END: u8 = 0;
+
+    /// Returns the type id of this tag.
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    /// Returns the child tag with the given name if this tag is a compound.
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(compound) => compound.get(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the child tag with the given name if this tag is a compound.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Tag> {
+        match self {
+            Tag::Compound(compound) => compound.get_mut(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of any integer tag as an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(value) => Some(*value as i64),
+            Tag::Short(value) => Some(*value as i64),
+            Tag::Int(value) => Some(*value as i64),
+            Tag::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Tag::Byte(value) => serializer.serialize_i8(*value),
+            Tag::Short(value) => serializer.serialize_i16(*value),
+            Tag::Int(value) => serializer.serialize_i32(*value),
+            Tag::Long(value) => serializer.serialize_i64(*value),
+            Tag::Float(value) => serializer.serialize_f32(*value),
+            Tag::Double(value) => serializer.serialize_f64(*value),
+            Tag::ByteArray(values) => serialize_array(serializer, BYTE_ARRAY, &values.iter().map(|&value| value as u8).collect::<Vec<_>>()),
+            Tag::String(value) => serializer.serialize_str(value),
+            Tag::List(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            },
+            Tag::Compound(compound) => {
+                let mut map = serializer.serialize_map(Some(compound.len()))?;
+                for (name, value) in compound {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            },
+            Tag::IntArray(values) => serialize_array(serializer, INT_ARRAY, &values.iter().flat_map(|value| value.to_be_bytes()).collect::<Vec<_>>()),
+            Tag::LongArray(values) => serialize_array(serializer, LONG_ARRAY, &values.iter().flat_map(|value| value.to_be_bytes()).collect::<Vec<_>>()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TagVisitor;
+
+        impl<'de> Visitor<'de> for TagVisitor {
+            type Value = Tag;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result { formatter.write_str("an nbt tag") }
+
+            /// The variant is the type id of the tag, the payload its value.
+            fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+            where
+                A: EnumAccess<'de>,
+            {
+                let (id, payload): (u8, _) = data.variant()?;
+                Ok(match id {
+                    1 => Tag::Byte(payload.newtype_variant()?),
+                    2 => Tag::Short(payload.newtype_variant()?),
+                    3 => Tag::Int(payload.newtype_variant()?),
+                    4 => Tag::Long(payload.newtype_variant()?),
+                    5 => Tag::Float(payload.newtype_variant()?),
+                    6 => Tag::Double(payload.newtype_variant()?),
+                    7 => Tag::ByteArray(payload.newtype_variant::<ByteArray>()?.0),
+                    8 => Tag::String(payload.newtype_variant()?),
+                    9 => Tag::List(payload.newtype_variant()?),
+                    10 => Tag::Compound(payload.newtype_variant()?),
+                    11 => Tag::IntArray(payload.newtype_variant::<IntArray>()?.0),
+                    12 => Tag::LongArray(payload.newtype_variant::<LongArray>()?.0),
+                    id => return Err(serde::de::Error::custom(NbtError::InvalidTagType(id))),
+                })
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TAG, TagVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ahash::RandomState;
+    use hashlink::LinkedHashMap;
+
+    use super::Tag;
+    use crate::nbt::{self, Compression, NbtError};
+
+    #[test]
+    fn test_tag_round_trip() {
+        let mut compound = LinkedHashMap::with_hasher(RandomState::default());
+        compound.insert("byte".to_string(), Tag::Byte(-3));
+        compound.insert("string".to_string(), Tag::String("minecraft:stone".to_string()));
+        compound.insert("list".to_string(), Tag::List(vec![Tag::Int(1), Tag::Int(2)]));
+        compound.insert("empty".to_string(), Tag::List(vec![]));
+        compound.insert("bytes".to_string(), Tag::ByteArray(vec![1, -1]));
+        compound.insert("byte_list".to_string(), Tag::List(vec![Tag::Byte(1), Tag::Byte(-1)]));
+        compound.insert("longs".to_string(), Tag::LongArray(vec![i64::MIN]));
+        let tag = Tag::Compound(compound);
+
+        let mut data = Vec::new();
+        nbt::write(&mut data, "root", &tag, Compression::None).unwrap();
+        assert_eq!(&data[..7], &[10, 0, 4, b'r', b'o', b'o', b't']);
+        assert_eq!(nbt::read(&data).unwrap(), ("root".to_string(), tag));
+
+        let huge = [7, 0x7F, 0xFF, 0xFF, 0xFF, 1, 2, 3];
+        assert!(matches!(nbt::from_slice_network::<Tag>(&huge), Err(NbtError::Eof)));
+        assert!(matches!(nbt::read(&[3, 0, 0, 0, 0, 0, 1]), Err(NbtError::InvalidRoot(3))));
+    }
+}
@@ -0,0 +1,244 @@
+//! Packets from the `packets.json` report of the Minecraft generators, which
+//! exists since 1.20.2.
+use std::fmt::{Display, Formatter};
+
+use ahash::RandomState;
+use hashlink::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::blocks::intermediary::MetaData;
+use crate::util::identifier::Identifier;
+
+/// The packets of a single direction mapped by their identifier.
+pub type RawDirection<'raw> = LinkedHashMap<Identifier<'raw>, RawPacket, RandomState>;
+/// The `packets.json` report, every protocol state mapped to its directions
+/// (`clientbound` and `serverbound`).
+pub type RawPackets<'raw> = LinkedHashMap<&'raw str, LinkedHashMap<&'raw str, RawDirection<'raw>, RandomState>, RandomState>;
+
+#[derive(Debug, Deserialize)]
+pub struct RawPacket {
+    pub protocol_id: i32,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PacketError {
+    #[error("No {state} {direction} packet has protocol id {id}, although higher ids are used")]
+    Gap {
+        state: String,
+        direction: String,
+        id: i32,
+    },
+    #[error("Multiple {state} {direction} packets have protocol id {id}: {packets:?}")]
+    Duplicate {
+        state: String,
+        direction: String,
+        id: i32,
+        packets: Vec<String>,
+    },
+}
+
+/// The compact packets format.
+///
+/// For every protocol state and direction, the packets are listed in the order
+/// of their protocol id, so the index of a packet is its protocol id.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PacketList<'raw> {
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<MetaData<'raw>>,
+    #[serde(borrow)]
+    pub states: LinkedHashMap<&'raw str, LinkedHashMap<&'raw str, Vec<Identifier<'raw>>, RandomState>, RandomState>,
+}
+
+impl<'raw> PacketList<'raw> {
+    /// Compacts the `packets.json` report, the protocol ids of every state
+    /// and direction need to be free of gaps and duplicates.
+    pub fn compact(metadata: Option<MetaData<'raw>>, raw: &RawPackets<'raw>) -> Result<Self, PacketError> {
+        let mut states = LinkedHashMap::with_hasher(RandomState::default());
+        for (&state, directions) in raw {
+            let mut compacted = LinkedHashMap::with_hasher(RandomState::default());
+            for (&direction, packets) in directions {
+                let mut packets: Vec<(i32, Identifier<'raw>)> = packets.iter().map(|(packet, raw)| (raw.protocol_id, *packet)).collect();
+                packets.sort_by_key(|(id, _)| *id);
+                for (expected, group) in packets.chunk_by(|a, b| a.0 == b.0).enumerate() {
+                    let id = group[0].0;
+                    if group.len() > 1 {
+                        return Err(PacketError::Duplicate {
+                            state: state.to_string(),
+                            direction: direction.to_string(),
+                            id,
+                            packets: group.iter().map(|(_, packet)| packet.to_string()).collect(),
+                        });
+                    }
+                    if id != expected as i32 {
+                        return Err(PacketError::Gap {
+                            state: state.to_string(),
+                            direction: direction.to_string(),
+                            id: expected as i32,
+                        });
+                    }
+                }
+                compacted.insert(direction, packets.into_iter().map(|(_, packet)| packet).collect());
+            }
+            states.insert(state, compacted);
+        }
+        Ok(Self { metadata, states })
+    }
+
+    /// Returns the protocol id of a packet.
+    pub fn id(&self, state: &str, direction: &str, packet: &Identifier) -> Option<i32> {
+        self.states
+            .get(state)?
+            .get(direction)?
+            .iter()
+            .position(|x| x == packet)
+            .map(|id| id as i32)
+    }
+}
+
+/// How a packet changed between two versions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum PacketChangeKind {
+    Added {
+        id: i32,
+    },
+    Removed {
+        id: i32,
+    },
+    Renumbered {
+        from: i32,
+        to: i32,
+    },
+}
+
+/// A packet that changed between two versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PacketChange<'raw> {
+    pub state: &'raw str,
+    pub direction: &'raw str,
+    pub packet: Identifier<'raw>,
+    #[serde(flatten)]
+    pub kind: PacketChangeKind,
+}
+
+impl<'raw> Display for PacketChange<'raw> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}: ", self.state, self.direction, self.packet)?;
+        match self.kind {
+            PacketChangeKind::Added { id } => write!(f, "added as {:#04x}", id),
+            PacketChangeKind::Removed { id } => write!(f, "removed, was {:#04x}", id),
+            PacketChangeKind::Renumbered { from, to } => write!(f, "renumbered from {:#04x} to {:#04x}", from, to),
+        }
+    }
+}
+
+/// Lists every packet that was added, removed or renumbered between two
+/// versions, in the order of the states and directions of `to`.
+pub fn diff<'raw>(from: &PacketList<'raw>, to: &PacketList<'raw>) -> Vec<PacketChange<'raw>> {
+    let mut changes = Vec::new();
+    let mut directions: Vec<(&'raw str, &'raw str)> = Vec::new();
+    for list in [to, from] {
+        for (&state, list_directions) in &list.states {
+            for &direction in list_directions.keys() {
+                if !directions.contains(&(state, direction)) {
+                    directions.push((state, direction));
+                }
+            }
+        }
+    }
+
+    for (state, direction) in directions {
+        let packets = |list: &PacketList<'raw>| -> Vec<Identifier<'raw>> {
+            list.states
+                .get(state)
+                .and_then(|directions| directions.get(direction))
+                .cloned()
+                .unwrap_or_default()
+        };
+        let (old, new) = (packets(from), packets(to));
+        let change = |packet: Identifier<'raw>, kind| PacketChange {
+            state,
+            direction,
+            packet,
+            kind,
+        };
+
+        for (id, packet) in new.iter().enumerate() {
+            let id = id as i32;
+            match old.iter().position(|x| x == packet) {
+                None => changes.push(change(*packet, PacketChangeKind::Added { id })),
+                Some(old_id) if old_id as i32 != id => changes.push(change(*packet, PacketChangeKind::Renumbered {
+                    from: old_id as i32,
+                    to: id,
+                })),
+                Some(_) => {},
+            }
+        }
+        for (id, packet) in old.iter().enumerate() {
+            if !new.contains(packet) {
+                changes.push(change(*packet, PacketChangeKind::Removed { id: id as i32 }));
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, PacketChangeKind, PacketError, PacketList, RawPackets};
+    use crate::util::identifier::Identifier;
+
+    const PACKETS: &str = r#"{
+        "handshake": { "serverbound": { "minecraft:intention": { "protocol_id": 0 } } },
+        "play": {
+            "clientbound": {
+                "minecraft:bundle_delimiter": { "protocol_id": 0 },
+                "minecraft:add_entity": { "protocol_id": 1 },
+                "minecraft:animate": { "protocol_id": 2 }
+            },
+            "serverbound": {
+                "minecraft:chat": { "protocol_id": 1 },
+                "minecraft:accept_teleportation": { "protocol_id": 0 }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_compact() {
+        let raw: RawPackets = serde_json::from_str(PACKETS).unwrap();
+        let list = PacketList::compact(None, &raw).unwrap();
+        assert_eq!(list.states["play"]["serverbound"], vec![Identifier::from_location("accept_teleportation"), Identifier::from_location("chat")]);
+        assert_eq!(list.id("play", "clientbound", &Identifier::from_location("animate")), Some(2));
+
+        let raw: RawPackets = serde_json::from_str(r#"{ "status": { "clientbound": { "a": { "protocol_id": 0 }, "b": { "protocol_id": 2 } } } }"#).unwrap();
+        assert!(matches!(PacketList::compact(None, &raw), Err(PacketError::Gap { id: 1, .. })));
+    }
+
+    #[test]
+    fn test_diff() {
+        let from = PacketList::compact(None, &serde_json::from_str(PACKETS).unwrap()).unwrap();
+        let to: PacketList = serde_json::from_str(
+            r#"{
+                "states": {
+                    "handshake": { "serverbound": ["intention"] },
+                    "play": {
+                        "clientbound": ["bundle_delimiter", "add_entity", "add_experience_orb", "animate"],
+                        "serverbound": ["accept_teleportation"]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let changes: Vec<_> = diff(&from, &to)
+            .into_iter()
+            .map(|change| (change.packet.location(), change.kind))
+            .collect();
+        assert_eq!(changes, vec![
+            ("add_experience_orb", PacketChangeKind::Added { id: 2 }),
+            ("animate", PacketChangeKind::Renumbered { from: 2, to: 3 }),
+            ("chat", PacketChangeKind::Removed { id: 1 }),
+        ]);
+    }
+}
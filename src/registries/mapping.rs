@@ -0,0 +1,270 @@
+use ahash::{AHashMap, RandomState};
+use hashlink::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{Registry, RegistryList};
+use crate::blocks::intermediary::MetaData;
+use crate::blocks::mapping::MappingArray;
+use crate::util::identifier::Identifier;
+
+/// Rules for mapping the entries of registries between two versions, per
+/// registry.
+///
+/// ```json
+/// {
+///     "minecraft:item": {
+///         "renames": { "grass_path": "dirt_path" },
+///         "fallbacks": { "copper_ingot": "iron_ingot" },
+///         "default": "stone"
+///     }
+/// }
+/// ```
+pub type RegistryMappingRules<'raw> = LinkedHashMap<Identifier<'raw>, RegistryRules<'raw>, RandomState>;
+
+/// How the entries of a single registry changed between two versions.
+///
+/// An entry is mapped to the entry it is renamed to in `renames`, otherwise
+/// to the entry with the same identifier. If that entry doesn't exist in the
+/// target version, the entry from `fallbacks` is used, and then `default`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegistryRules<'raw> {
+    #[serde(borrow, default)]
+    pub renames: LinkedHashMap<Identifier<'raw>, Identifier<'raw>, RandomState>,
+    #[serde(borrow, default)]
+    pub fallbacks: LinkedHashMap<Identifier<'raw>, Identifier<'raw>, RandomState>,
+    #[serde(borrow, default)]
+    pub default: Option<Identifier<'raw>>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RegistryMappingError {
+    #[error("Invalid {rule} \"{target}\" for registry \"{registry}\": unknown entry")]
+    UnknownEntry {
+        registry: String,
+        rule: &'static str,
+        target: String,
+    },
+}
+
+/// How an entry without a counterpart was resolved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistryFallbackStrategy {
+    /// The fallback specified for the entry
+    Explicit,
+    /// The default of the registry rules
+    Default,
+}
+
+/// An entry resolved using a fallback.
+#[derive(Debug, Serialize)]
+pub struct RegistryFallback<'raw> {
+    pub id: i32,
+    pub entry: Identifier<'raw>,
+    pub target: i32,
+    pub strategy: RegistryFallbackStrategy,
+}
+
+/// An entry without a counterpart in the target version.
+#[derive(Debug, Serialize)]
+pub struct UnmappedEntry<'raw> {
+    pub id: i32,
+    pub entry: Identifier<'raw>,
+}
+
+/// The id mapping of a single registry.
+///
+/// `ids` is indexed by the protocol id of the source version and contains the
+/// protocol id of the target version, or `null` for unmapped entries. Entries
+/// resolved using a fallback are listed in `ids` as well.
+#[derive(Debug, Serialize)]
+pub struct RegistryIdMapping<'raw> {
+    pub ids: Vec<Option<i32>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<RegistryFallback<'raw>>,
+    pub unmapped: Vec<UnmappedEntry<'raw>>,
+}
+
+/// Id mappings for every registry present in both versions.
+#[derive(Debug, Serialize)]
+pub struct RegistryMapping<'raw> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<MetaData<'raw>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<MetaData<'raw>>,
+    pub registries: LinkedHashMap<Identifier<'raw>, RegistryIdMapping<'raw>, RandomState>,
+    /// Registries of the source version that don't exist in the target
+    /// version
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<Identifier<'raw>>,
+}
+
+impl<'raw> RegistryMapping<'raw> {
+    /// Maps the entries of every registry of `from` to the entries of `to`.
+    ///
+    /// Every rename, fallback and default of the rules has to be an entry of
+    /// the target registry.
+    pub fn new(from: &RegistryList<'raw>, to: &RegistryList<'raw>, rules: Option<&RegistryMappingRules<'raw>>) -> Result<Self, RegistryMappingError> {
+        let mut registries = LinkedHashMap::with_hasher(RandomState::default());
+        let mut missing = Vec::new();
+        for (identifier, registry) in &from.registries {
+            match to.registries.get(identifier) {
+                Some(target) => {
+                    let rules = rules.and_then(|rules| rules.get(identifier));
+                    registries.insert(*identifier, map_registry(identifier, registry, target, rules)?);
+                },
+                None => missing.push(*identifier),
+            }
+        }
+        Ok(Self {
+            from: from.metadata.clone(),
+            to: to.metadata.clone(),
+            registries,
+            missing,
+        })
+    }
+}
+
+impl<'raw> RegistryIdMapping<'raw> {
+    /// The mapping as a flat array, both versions need metadata.
+    ///
+    /// Registry mappings reuse the binary format of blockstate mappings, the
+    /// entries are protocol ids of the registry instead of blockstate ids.
+    pub fn array(&self, from: &MetaData, to: &MetaData) -> MappingArray {
+        MappingArray {
+            from: from.id,
+            to: to.id,
+            states: self.ids.iter().map(|id| id.map(|id| id as u32)).collect(),
+        }
+    }
+}
+
+fn map_registry<'raw>(
+    identifier: &Identifier<'raw>,
+    registry: &Registry<'raw>,
+    target: &Registry<'raw>,
+    rules: Option<&RegistryRules<'raw>>,
+) -> Result<RegistryIdMapping<'raw>, RegistryMappingError> {
+    let ids: AHashMap<Identifier, i32> = target.entries.iter().enumerate().map(|(id, entry)| (*entry, id as i32)).collect();
+    if let Some(rules) = rules {
+        let targets = rules
+            .renames
+            .values()
+            .map(|target| ("rename", target))
+            .chain(rules.fallbacks.values().map(|target| ("fallback", target)))
+            .chain(rules.default.iter().map(|target| ("default", target)));
+        for (rule, target) in targets {
+            if !ids.contains_key(target) {
+                return Err(RegistryMappingError::UnknownEntry {
+                    registry: identifier.to_string(),
+                    rule,
+                    target: target.to_string(),
+                });
+            }
+        }
+    }
+
+    let mut mapping = RegistryIdMapping {
+        ids: Vec::with_capacity(registry.entries.len()),
+        fallbacks: Vec::new(),
+        unmapped: Vec::new(),
+    };
+
+    for (id, entry) in registry.entries.iter().enumerate() {
+        let id = id as i32;
+        let renamed = rules.and_then(|rules| rules.renames.get(entry)).unwrap_or(entry);
+        if let Some(&target) = ids.get(renamed) {
+            mapping.ids.push(Some(target));
+            continue;
+        }
+
+        let explicit = rules
+            .and_then(|rules| rules.fallbacks.get(entry))
+            .map(|fallback| (ids[fallback], RegistryFallbackStrategy::Explicit));
+        let default = rules
+            .and_then(|rules| rules.default.as_ref())
+            .map(|default| (ids[default], RegistryFallbackStrategy::Default));
+        match explicit.or(default) {
+            Some((target, strategy)) => {
+                mapping.ids.push(Some(target));
+                mapping.fallbacks.push(RegistryFallback {
+                    id,
+                    entry: *entry,
+                    target,
+                    strategy,
+                });
+            },
+            None => {
+                mapping.ids.push(None);
+                mapping.unmapped.push(UnmappedEntry { id, entry: *entry });
+            },
+        }
+    }
+    Ok(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RegistryFallbackStrategy, RegistryMapping, RegistryMappingError, RegistryMappingRules};
+    use crate::registries::RegistryList;
+    use crate::util::identifier::Identifier;
+
+    #[test]
+    fn test_registry_mapping() {
+        let from: RegistryList = serde_json::from_str(
+            r#"{
+                "metadata": { "id": 1 },
+                "registries": {
+                    "minecraft:item": { "id": 0, "entries": ["air", "grass_path", "copper_ingot", "amethyst", "stone"] },
+                    "minecraft:menu": { "id": 1, "entries": ["anvil"] }
+                }
+            }"#,
+        )
+        .unwrap();
+        let to: RegistryList = serde_json::from_str(
+            r#"{
+                "metadata": { "id": 2 },
+                "registries": {
+                    "minecraft:item": { "id": 0, "entries": ["air", "stone", "iron_ingot", "dirt_path"] }
+                }
+            }"#,
+        )
+        .unwrap();
+        let rules: RegistryMappingRules = serde_json::from_str(
+            r#"{
+                "minecraft:item": {
+                    "renames": { "grass_path": "dirt_path" },
+                    "fallbacks": { "copper_ingot": "iron_ingot" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mapping = RegistryMapping::new(&from, &to, Some(&rules)).unwrap();
+        assert_eq!(mapping.missing, vec![Identifier::from_location("menu")]);
+        let items = &mapping.registries[&Identifier::from_location("item")];
+        assert_eq!(items.ids, vec![Some(0), Some(3), Some(2), None, Some(1)]);
+        assert_eq!(items.fallbacks.len(), 1);
+        assert_eq!(items.fallbacks[0].strategy, RegistryFallbackStrategy::Explicit);
+        assert_eq!(items.unmapped[0].entry, Identifier::from_location("amethyst"));
+
+        let array = items.array(from.metadata.as_ref().unwrap(), to.metadata.as_ref().unwrap());
+        assert_eq!(array.get(1), Some(3));
+        assert_eq!(array.get(3), None);
+
+        for (rules, rule, target) in [
+            (r#"{ "minecraft:item": { "renames": { "grass_path": "grass_block" } } }"#, "rename", "minecraft:grass_block"),
+            (r#"{ "minecraft:item": { "fallbacks": { "copper_ingot": "gold_ingot" } } }"#, "fallback", "minecraft:gold_ingot"),
+            (r#"{ "minecraft:item": { "default": "barrier" } }"#, "default", "minecraft:barrier"),
+        ] {
+            let rules: RegistryMappingRules = serde_json::from_str(rules).unwrap();
+            assert_eq!(RegistryMapping::new(&from, &to, Some(&rules)).unwrap_err(), RegistryMappingError::UnknownEntry {
+                registry: "minecraft:item".to_string(),
+                rule,
+                target: target.to_string()
+            });
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Registries from the `registries.json` report of the Minecraft generators,
+//! like items, entity types and sound events.
+use ahash::RandomState;
+use hashlink::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::blocks::intermediary::MetaData;
+use crate::util::identifier::Identifier;
+
+pub mod mapping;
+
+/// The `registries.json` report, every registry mapped by its identifier.
+pub type RawRegistries<'raw> = LinkedHashMap<Identifier<'raw>, RawRegistry<'raw>, RandomState>;
+
+/// A single registry as generated by Minecraft.
+#[derive(Debug, Deserialize)]
+pub struct RawRegistry<'raw> {
+    #[serde(borrow, default)]
+    pub default: Option<Identifier<'raw>>,
+    pub protocol_id: i32,
+    #[serde(borrow)]
+    pub entries: LinkedHashMap<Identifier<'raw>, RawEntry, RandomState>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawEntry {
+    pub protocol_id: i32,
+}
+
+/// A problem preventing a registry from being compacted.
+#[derive(Debug, Clone, Error, PartialEq, Eq, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum RegistryIssue {
+    /// No entry has these protocol ids, although higher ids are used.
+    #[error("Registry \"{registry}\" has no entries for the protocol ids {start}..{end}")]
+    Gap {
+        registry: String,
+        start: i32,
+        end: i32,
+    },
+    /// Multiple entries share the same protocol id.
+    #[error("Registry \"{registry}\" has multiple entries for protocol id {id}: {entries:?}")]
+    Duplicate {
+        registry: String,
+        id: i32,
+        entries: Vec<String>,
+    },
+    /// The default entry isn't part of the registry.
+    #[error("Registry \"{registry}\" has an unknown default entry \"{default}\"")]
+    UnknownDefault {
+        registry: String,
+        default: String,
+    },
+}
+
+/// Checks every registry for gaps and duplicates in its protocol ids, and for
+/// a default entry that isn't part of the registry.
+pub fn healthcheck(registries: &RawRegistries) -> Vec<RegistryIssue> {
+    registries
+        .iter()
+        .flat_map(|(identifier, registry)| check_registry(identifier, registry))
+        .collect()
+}
+
+fn check_registry(identifier: &Identifier, registry: &RawRegistry) -> Vec<RegistryIssue> {
+    let mut issues = Vec::new();
+    let mut entries: Vec<(i32, &Identifier)> = registry.entries.iter().map(|(entry, raw)| (raw.protocol_id, entry)).collect();
+    entries.sort_by_key(|(id, _)| *id);
+
+    let mut expected = 0;
+    for group in entries.chunk_by(|a, b| a.0 == b.0) {
+        let id = group[0].0;
+        if id != expected {
+            issues.push(RegistryIssue::Gap {
+                registry: identifier.to_string(),
+                start: expected,
+                end: id,
+            });
+        }
+        if group.len() > 1 {
+            issues.push(RegistryIssue::Duplicate {
+                registry: identifier.to_string(),
+                id,
+                entries: group.iter().map(|(_, entry)| entry.to_string()).collect(),
+            });
+        }
+        expected = id + 1;
+    }
+    if let Some(default) = registry.default.filter(|default| !registry.entries.contains_key(default)) {
+        issues.push(RegistryIssue::UnknownDefault {
+            registry: identifier.to_string(),
+            default: default.to_string(),
+        });
+    }
+    issues
+}
+
+/// The compact registries format.
+///
+/// The entries of every registry are listed in the order of their protocol
+/// id, so the index of an entry is its protocol id.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegistryList<'raw> {
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<MetaData<'raw>>,
+    #[serde(borrow)]
+    pub registries: LinkedHashMap<Identifier<'raw>, Registry<'raw>, RandomState>,
+}
+
+/// A compacted registry.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Registry<'raw> {
+    #[serde(rename = "id")]
+    pub protocol_id: i32,
+    #[serde(borrow, skip_serializing_if = "Option::is_none", default)]
+    pub default: Option<Identifier<'raw>>,
+    #[serde(borrow)]
+    pub entries: Vec<Identifier<'raw>>,
+}
+
+impl<'raw> Registry<'raw> {
+    /// Returns the protocol id of an entry.
+    pub fn id(&self, entry: &Identifier) -> Option<i32> { self.entries.iter().position(|x| x == entry).map(|index| index as i32) }
+
+    /// Returns the entry with the given protocol id.
+    pub fn entry(&self, id: i32) -> Option<&Identifier<'raw>> { usize::try_from(id).ok().and_then(|id| self.entries.get(id)) }
+}
+
+impl<'raw> RegistryList<'raw> {
+    /// Compacts the registries of the `registries.json` report, fails with
+    /// the first issue [`healthcheck`] would find.
+    pub fn compact(metadata: Option<MetaData<'raw>>, raw: &RawRegistries<'raw>) -> Result<Self, RegistryIssue> {
+        let registries = raw
+            .iter()
+            .map(|(identifier, registry)| {
+                if let Some(issue) = check_registry(identifier, registry).into_iter().next() {
+                    return Err(issue);
+                }
+                let mut entries: Vec<(i32, Identifier<'raw>)> = registry.entries.iter().map(|(entry, raw)| (raw.protocol_id, *entry)).collect();
+                entries.sort_by_key(|(id, _)| *id);
+                let registry = Registry {
+                    protocol_id: registry.protocol_id,
+                    default: registry.default,
+                    entries: entries.into_iter().map(|(_, entry)| entry).collect(),
+                };
+                Ok((*identifier, registry))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            metadata,
+            registries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{healthcheck, RawRegistries, RegistryIssue, RegistryList};
+    use crate::blocks::intermediary::MetaData;
+    use crate::util::identifier::Identifier;
+
+    const REGISTRIES: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/registries.json"));
+
+    #[test]
+    fn test_compact() {
+        let raw: RawRegistries = serde_json::from_str(REGISTRIES).unwrap();
+        assert_eq!(healthcheck(&raw), vec![]);
+
+        let list = RegistryList::compact(Some(MetaData::new(2730, None, None)), &raw).unwrap();
+        let entities = &list.registries[&Identifier::from_location("entity_type")];
+        assert_eq!(entities.entries, vec![Identifier::from_location("pig"), Identifier::from_location("zombie"), Identifier::from_location("cow")]);
+        assert_eq!(entities.id(&Identifier::from_location("cow")), Some(2));
+        assert_eq!(list.registries[&Identifier::from_location("item")].entry(4), Some(&Identifier::from_location("hopper")));
+
+        let data = serde_json::to_string(&list).unwrap();
+        let loaded: RegistryList = serde_json::from_str(&data).unwrap();
+        assert_eq!(loaded.registries.len(), 3);
+    }
+
+    #[test]
+    fn test_healthcheck() {
+        let raw: RawRegistries = serde_json::from_str(
+            r#"{
+                "minecraft:sound_event": {
+                    "default": "minecraft:missing",
+                    "protocol_id": 0,
+                    "entries": {
+                        "minecraft:a": { "protocol_id": 1 },
+                        "minecraft:b": { "protocol_id": 1 },
+                        "minecraft:c": { "protocol_id": 4 }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let issues = healthcheck(&raw);
+        let registry = "minecraft:sound_event".to_string();
+        assert_eq!(issues, vec![
+            RegistryIssue::Gap {
+                registry: registry.clone(),
+                start: 0,
+                end: 1
+            },
+            RegistryIssue::Duplicate {
+                registry: registry.clone(),
+                id: 1,
+                entries: vec!["minecraft:a".to_string(), "minecraft:b".to_string()]
+            },
+            RegistryIssue::Gap {
+                registry: registry.clone(),
+                start: 2,
+                end: 4
+            },
+            RegistryIssue::UnknownDefault {
+                registry,
+                default: "minecraft:missing".to_string()
+            },
+        ]);
+        assert!(RegistryList::compact(None, &raw).is_err());
+    }
+}
@@ -0,0 +1,377 @@
+use std::io::Write;
+
+use ahash::{AHashMap, RandomState};
+use hashlink::LinkedHashMap;
+use thiserror::Error;
+
+use crate::blocks::intermediary::lookup::{BlockStateIndex, LookupError};
+use crate::blocks::mapping::StateMapping;
+use crate::nbt::{self, Compression, NbtError, Tag};
+use crate::util::blockstate::{BlockStateParseError, BlockStateString};
+
+#[derive(Debug, Error)]
+pub enum SchematicError {
+    #[error("Invalid nbt data")]
+    Nbt(#[from] NbtError),
+    #[error("Missing or invalid tag \"{0}\"")]
+    InvalidTag(&'static str),
+    #[error("Unsupported schematic version {0}")]
+    UnsupportedVersion(i64),
+    #[error("Invalid varint in block data")]
+    InvalidVarint,
+    #[error("Unknown blockstate id {0} in the target version")]
+    UnknownState(i32),
+    #[error("Block data refers to unknown palette index {0}")]
+    UnknownPaletteIndex(i32),
+}
+
+/// The reason a palette entry could not be converted.
+#[derive(Debug, Error)]
+pub enum PaletteError {
+    #[error("{0}")]
+    Parse(#[from] BlockStateParseError),
+    #[error("{0}")]
+    Lookup(#[from] LookupError),
+    #[error("No counterpart in the target version")]
+    Unmapped,
+}
+
+/// A Sponge schematic of version 2 or 3.
+///
+/// Only the block palette and block data are interpreted, all other tags are
+/// kept as they are.
+#[derive(Debug)]
+pub struct SpongeSchematic {
+    name: String,
+    root: Tag,
+    version: i64,
+}
+
+impl SpongeSchematic {
+    /// Reads a (possibly gzipped) schematic.
+    pub fn read(data: &[u8]) -> Result<Self, SchematicError> {
+        let (name, root) = nbt::read(data)?;
+        let version = match root.get("Schematic") {
+            Some(schematic) => schematic.get("Version"),
+            None => root.get("Version"),
+        }
+        .and_then(Tag::as_i64)
+        .ok_or(SchematicError::InvalidTag("Version"))?;
+        if !(2..=3).contains(&version) {
+            return Err(SchematicError::UnsupportedVersion(version));
+        }
+        Ok(Self {
+            name,
+            root,
+            version,
+        })
+    }
+
+    /// Writes the schematic using gzip compression.
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), SchematicError> { Ok(nbt::write(writer, &self.name, &self.root, Compression::Gzip)?) }
+
+    pub fn version(&self) -> i64 { self.version }
+
+    pub fn data_version(&self) -> Option<i64> { self.schematic().get("DataVersion").and_then(Tag::as_i64) }
+
+    pub fn set_data_version(&mut self, data_version: i32) { insert(self.schematic_mut(), "DataVersion", Tag::Int(data_version)) }
+
+    /// Returns the block palette, every entry is a blockstate string mapped
+    /// to its index in the block data.
+    pub fn palette(&self) -> Result<Vec<(&str, i32)>, SchematicError> {
+        let (palette, tag) = match self.version {
+            2 => (self.schematic().get("Palette"), "Palette"),
+            _ => (self.blocks()?.get("Palette"), "Blocks.Palette"),
+        };
+        match palette {
+            Some(Tag::Compound(palette)) => palette
+                .iter()
+                .map(|(state, index)| match index {
+                    Tag::Int(index) => Ok((state.as_str(), *index)),
+                    _ => Err(SchematicError::InvalidTag(tag)),
+                })
+                .collect(),
+            _ => Err(SchematicError::InvalidTag(tag)),
+        }
+    }
+
+    /// Returns the palette index of every block in the schematic.
+    pub fn block_data(&self) -> Result<Vec<i32>, SchematicError> {
+        let (data, tag) = match self.version {
+            2 => (self.schematic().get("BlockData"), "BlockData"),
+            _ => (self.blocks()?.get("Data"), "Blocks.Data"),
+        };
+        match data {
+            Some(Tag::ByteArray(data)) => read_varints(data),
+            _ => Err(SchematicError::InvalidTag(tag)),
+        }
+    }
+
+    /// Replaces the block palette and block data.
+    pub fn set_blocks(&mut self, palette: &[String], data: &[i32]) -> Result<(), SchematicError> {
+        let mut compound = LinkedHashMap::with_hasher(RandomState::default());
+        for (index, state) in palette.iter().enumerate() {
+            compound.insert(state.clone(), Tag::Int(index as i32));
+        }
+        let palette = Tag::Compound(compound);
+        let data = Tag::ByteArray(write_varints(data));
+
+        if self.version == 2 {
+            let schematic = self.schematic_mut();
+            insert(schematic, "PaletteMax", Tag::Int(palette_len(&palette)));
+            insert(schematic, "Palette", palette);
+            insert(schematic, "BlockData", data);
+        } else {
+            let blocks = match self.schematic_mut().get_mut("Blocks") {
+                Some(blocks @ Tag::Compound(_)) => blocks,
+                _ => return Err(SchematicError::InvalidTag("Blocks")),
+            };
+            insert(blocks, "Palette", palette);
+            insert(blocks, "Data", data);
+        }
+        Ok(())
+    }
+
+    /// The compound containing the blocks of a version 3 schematic.
+    fn blocks(&self) -> Result<&Tag, SchematicError> {
+        match self.schematic().get("Blocks") {
+            Some(blocks @ Tag::Compound(_)) => Ok(blocks),
+            _ => Err(SchematicError::InvalidTag("Blocks")),
+        }
+    }
+
+    /// The compound containing the schematic data, version 3 nests this in
+    /// the root compound.
+    fn schematic(&self) -> &Tag {
+        match self.root.get("Schematic") {
+            Some(schematic) => schematic,
+            None => &self.root,
+        }
+    }
+
+    fn schematic_mut(&mut self) -> &mut Tag {
+        if self.root.get("Schematic").is_some() {
+            self.root.get_mut("Schematic").unwrap()
+        } else {
+            &mut self.root
+        }
+    }
+}
+
+/// Converts schematics from one version of block data to another.
+///
+/// Palette entries that can't be converted are replaced by a fallback
+/// blockstate from the target version.
+pub struct SchematicConverter<'a, 'b, 'raw> {
+    from: &'a BlockStateIndex<'b, 'raw>,
+    to: &'a BlockStateIndex<'b, 'raw>,
+    mapping: &'a StateMapping<'raw>,
+    fallback: i32,
+}
+
+impl<'a, 'b, 'raw> SchematicConverter<'a, 'b, 'raw> {
+    pub fn new(from: &'a BlockStateIndex<'b, 'raw>, to: &'a BlockStateIndex<'b, 'raw>, mapping: &'a StateMapping<'raw>, fallback: i32) -> Self {
+        Self {
+            from,
+            to,
+            mapping,
+            fallback,
+        }
+    }
+
+    /// Remaps the palette of the schematic, returning every palette entry
+    /// that had to be replaced by the fallback blockstate.
+    pub fn convert(&self, schematic: &mut SpongeSchematic) -> Result<Vec<(String, PaletteError)>, SchematicError> {
+        let mut unmapped = Vec::new();
+        let mut palette = Vec::new();
+        let mut palette_indices = AHashMap::new();
+        let mut remapped = AHashMap::new();
+
+        for (entry, index) in schematic.palette()? {
+            let id = match self.map_entry(entry) {
+                Ok(id) => id,
+                Err(error) => {
+                    unmapped.push((entry.to_string(), error));
+                    self.fallback
+                },
+            };
+            let state = self.to.state(id).ok_or(SchematicError::UnknownState(id))?;
+            let new_index = *palette_indices.entry(id).or_insert_with(|| {
+                palette.push(state.to_string());
+                palette.len() as i32 - 1
+            });
+            remapped.insert(index, new_index);
+        }
+
+        let data = schematic
+            .block_data()?
+            .into_iter()
+            .map(|index| remapped.get(&index).copied().ok_or(SchematicError::UnknownPaletteIndex(index)))
+            .collect::<Result<Vec<_>, _>>()?;
+        schematic.set_blocks(&palette, &data)?;
+
+        Ok(unmapped)
+    }
+
+    fn map_entry(&self, entry: &str) -> Result<i32, PaletteError> {
+        let state = BlockStateString::try_from(entry)?;
+        let id = self.from.resolve(&state)?;
+        self.mapping.states.get(&id).copied().ok_or(PaletteError::Unmapped)
+    }
+}
+
+fn insert(compound: &mut Tag, name: &str, tag: Tag) {
+    if let Tag::Compound(compound) = compound {
+        compound.replace(name.to_string(), tag);
+    }
+}
+
+fn palette_len(palette: &Tag) -> i32 {
+    match palette {
+        Tag::Compound(compound) => compound.len() as i32,
+        _ => 0,
+    }
+}
+
+fn read_varints(data: &[i8]) -> Result<Vec<i32>, SchematicError> {
+    let mut values = Vec::with_capacity(data.len());
+    let mut value = 0;
+    let mut shift = 0;
+    for &byte in data {
+        let byte = byte as u8;
+        value |= ((byte & 0x7F) as i32) << shift;
+        if byte & 0x80 == 0 {
+            values.push(value);
+            value = 0;
+            shift = 0;
+        } else {
+            shift += 7;
+            if shift > 28 {
+                return Err(SchematicError::InvalidVarint);
+            }
+        }
+    }
+    if shift != 0 {
+        return Err(SchematicError::InvalidVarint);
+    }
+    Ok(values)
+}
+
+fn write_varints(values: &[i32]) -> Vec<i8> {
+    let mut data = Vec::with_capacity(values.len());
+    for &value in values {
+        let mut value = value as u32;
+        loop {
+            if value & !0x7F == 0 {
+                data.push(value as u8 as i8);
+                break;
+            }
+            data.push(((value & 0x7F) | 0x80) as u8 as i8);
+            value >>= 7;
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::mapping::StateMapper;
+    use crate::ModernBlockList;
+
+    const FROM: &str = r#"{
+        "properties": { "axis": ["x", "y", "z"] },
+        "blocks": {
+            "minecraft:stone": { "base": 0 },
+            "minecraft:oak_log": { "properties": { "axis": "axis" }, "base": 1, "default": 2 }
+        }
+    }"#;
+    const TO: &str = r#"{
+        "metadata": { "id": 3465 },
+        "properties": { "axis": ["x", "y", "z"] },
+        "blocks": {
+            "minecraft:air": { "base": 0 },
+            "minecraft:stone": { "base": 1 },
+            "minecraft:oak_log": { "properties": { "axis": "axis" }, "base": 2, "default": 3 }
+        }
+    }"#;
+
+    fn compound(entries: Vec<(&str, Tag)>) -> Tag { Tag::Compound(entries.into_iter().map(|(name, tag)| (name.to_string(), tag)).collect()) }
+
+    /// Converts the schematic, writes it and reads it back.
+    fn convert(root: Tag) -> (SpongeSchematic, Vec<(String, PaletteError)>) {
+        let from: ModernBlockList = serde_json::from_str(FROM).unwrap();
+        let to: ModernBlockList = serde_json::from_str(TO).unwrap();
+        let mapping = StateMapper::new(&from, &to, None).map().unwrap();
+        let (from_index, to_index) = (from.index().unwrap(), to.index().unwrap());
+
+        let mut data = Vec::new();
+        nbt::write(&mut data, "Schematic", &root, Compression::Gzip).unwrap();
+        let mut schematic = SpongeSchematic::read(&data).unwrap();
+        let unmapped = SchematicConverter::new(&from_index, &to_index, &mapping, 0)
+            .convert(&mut schematic)
+            .unwrap();
+        schematic.set_data_version(3465);
+
+        let mut data = Vec::new();
+        schematic.write(&mut data).unwrap();
+        (SpongeSchematic::read(&data).unwrap(), unmapped)
+    }
+
+    fn blocks() -> Vec<(&'static str, Tag)> {
+        vec![
+            ("Palette", compound(vec![("minecraft:stone", Tag::Int(0)), ("minecraft:oak_log[axis=x]", Tag::Int(1)), ("minecraft:granite", Tag::Int(2))])),
+            ("Data", Tag::ByteArray(write_varints(&[0, 1, 1, 2, 0]))),
+        ]
+    }
+
+    fn assert_converted(schematic: &SpongeSchematic, unmapped: &[(String, PaletteError)]) {
+        assert_eq!(unmapped.len(), 1);
+        assert_eq!(unmapped[0].0, "minecraft:granite");
+        assert_eq!(schematic.data_version(), Some(3465));
+        assert_eq!(schematic.palette().unwrap(), vec![("minecraft:stone", 0), ("minecraft:oak_log[axis=x]", 1), ("minecraft:air", 2)]);
+        assert_eq!(schematic.block_data().unwrap(), vec![0, 1, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_convert_v2() {
+        let [(_, palette), (_, data)]: [(&str, Tag); 2] = blocks().try_into().unwrap();
+        let root = compound(vec![
+            ("Version", Tag::Int(2)),
+            ("DataVersion", Tag::Int(2586)),
+            ("Width", Tag::Short(5)),
+            ("PaletteMax", Tag::Int(3)),
+            ("Palette", palette),
+            ("BlockData", data),
+        ]);
+
+        let (schematic, unmapped) = convert(root);
+        assert_eq!(schematic.version(), 2);
+        assert_converted(&schematic, &unmapped);
+        assert_eq!(schematic.schematic().get("PaletteMax"), Some(&Tag::Int(3)));
+        assert_eq!(schematic.schematic().get("Width"), Some(&Tag::Short(5)));
+    }
+
+    #[test]
+    fn test_convert_v3() {
+        let schematic = compound(vec![("Version", Tag::Int(3)), ("DataVersion", Tag::Int(3120)), ("Blocks", compound(blocks()))]);
+
+        let (schematic, unmapped) = convert(compound(vec![("Schematic", schematic)]));
+        assert_eq!(schematic.version(), 3);
+        assert_converted(&schematic, &unmapped);
+
+        let mut schematic = schematic;
+        insert(schematic.schematic_mut(), "Blocks", Tag::Int(0));
+        assert!(matches!(schematic.block_data(), Err(SchematicError::InvalidTag("Blocks"))));
+        assert!(matches!(schematic.set_blocks(&[], &[]), Err(SchematicError::InvalidTag("Blocks"))));
+    }
+
+    #[test]
+    fn test_varints() {
+        let values = vec![0, 1, 127, 128, 300, 2097151, i32::MAX];
+        let data = write_varints(&values);
+        assert_eq!(&data[..5], &[0, 1, 127, -128, 1]);
+        assert_eq!(read_varints(&data).unwrap(), values);
+        assert!(read_varints(&[-128]).is_err());
+    }
+}
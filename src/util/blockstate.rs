@@ -0,0 +1,196 @@
+use std::fmt::{Display, Formatter};
+
+use nom::bytes::complete::take_while1;
+use nom::character::complete::{char, space0};
+use nom::error::Error;
+use nom::sequence::{delimited, terminated};
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+
+use super::identifier::{parse_identifier, Identifier};
+
+/// A blockstate in the vanilla string syntax, e.g.
+/// `minecraft:oak_stairs[facing=north,half=top]`.
+///
+/// The properties don't have to be complete, resolving this string against
+/// block data fills in the missing properties from the default blockstate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockStateString<'a> {
+    identifier: Identifier<'a>,
+    properties: Vec<(&'a str, &'a str)>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{kind} at position {position}")]
+pub struct BlockStateParseError {
+    pub position: usize,
+    pub kind: BlockStateParseErrorKind,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BlockStateParseErrorKind {
+    #[error("invalid character in identifier")]
+    InvalidIdentifier,
+    #[error("expected a property name")]
+    ExpectedName,
+    #[error("expected '='")]
+    ExpectedEquals,
+    #[error("expected a property value")]
+    ExpectedValue,
+    #[error("expected ',' or ']'")]
+    ExpectedSeparator,
+    #[error("duplicate property")]
+    DuplicateProperty,
+    #[error("unexpected characters after blockstate")]
+    TrailingCharacters,
+}
+
+impl<'a> BlockStateString<'a> {
+    pub fn new(identifier: Identifier<'a>, properties: Vec<(&'a str, &'a str)>) -> Self {
+        Self {
+            identifier,
+            properties,
+        }
+    }
+
+    pub fn identifier(&self) -> &Identifier<'a> { &self.identifier }
+
+    pub fn properties(&self) -> &[(&'a str, &'a str)] { &self.properties }
+}
+
+impl<'a> Display for BlockStateString<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.identifier)?;
+        if !self.properties.is_empty() {
+            f.write_str("[")?;
+            for (index, (name, value)) in self.properties.iter().enumerate() {
+                if index != 0 {
+                    f.write_str(",")?;
+                }
+                write!(f, "{}={}", name, value)?;
+            }
+            f.write_str("]")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for BlockStateString<'a> {
+    type Error = BlockStateParseError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        let error = |rest: &str, kind| BlockStateParseError {
+            position: input.len() - rest.len(),
+            kind,
+        };
+        let token = |rest| delimited(space0::<_, Error<&'a str>>, take_while1(|i| "0123456789abcdefghijklmnopqrstuvwxyz_".contains(i)), space0)(rest);
+        let symbol = |c, rest| terminated(char::<_, Error<&'a str>>(c), space0)(rest);
+
+        let (mut rest, identifier) = parse_identifier(input).unwrap();
+        if identifier.location().is_empty() || !(rest.is_empty() || rest.starts_with('[')) {
+            return Err(error(rest, BlockStateParseErrorKind::InvalidIdentifier));
+        }
+
+        let mut properties: Vec<(&'a str, &'a str)> = Vec::new();
+        if let Ok((remaining, _)) = symbol('[', rest) {
+            rest = remaining;
+            if let Ok((remaining, _)) = symbol(']', rest) {
+                rest = remaining;
+            } else {
+                loop {
+                    let (remaining, name) = token(rest).map_err(|_| error(rest, BlockStateParseErrorKind::ExpectedName))?;
+                    if properties.iter().any(|(property, _)| *property == name) {
+                        return Err(error(rest.trim_start(), BlockStateParseErrorKind::DuplicateProperty));
+                    }
+                    let (remaining, _) = symbol('=', remaining).map_err(|_| error(remaining, BlockStateParseErrorKind::ExpectedEquals))?;
+                    let (remaining, value) = token(remaining).map_err(|_| error(remaining, BlockStateParseErrorKind::ExpectedValue))?;
+                    properties.push((name, value));
+
+                    if let Ok((remaining, _)) = symbol(',', remaining) {
+                        rest = remaining;
+                    } else if let Ok((remaining, _)) = symbol(']', remaining) {
+                        rest = remaining;
+                        break;
+                    } else {
+                        return Err(error(remaining, BlockStateParseErrorKind::ExpectedSeparator));
+                    }
+                }
+            }
+        }
+
+        if !rest.is_empty() {
+            return Err(error(rest, BlockStateParseErrorKind::TrailingCharacters));
+        }
+        Ok(BlockStateString {
+            identifier,
+            properties,
+        })
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for BlockStateString<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let state = <&'a str as Deserialize>::deserialize(deserializer)?;
+        state.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+impl<'a> Serialize for BlockStateString<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, Token};
+
+    use super::*;
+
+    #[test]
+    fn test_blockstate_parse() {
+        let state = BlockStateString::try_from("minecraft:oak_stairs[facing=north,half=top]").unwrap();
+        assert_eq!(state, BlockStateString::new(Identifier::from_location("oak_stairs"), vec![("facing", "north"), ("half", "top")]));
+
+        let state = BlockStateString::try_from("stone").unwrap();
+        assert_eq!(state, BlockStateString::new(Identifier::from_location("stone"), vec![]));
+
+        let state = BlockStateString::try_from("test:wheat[ age = 7 ]").unwrap();
+        assert_eq!(state, BlockStateString::new(Identifier::from_full("test", "wheat"), vec![("age", "7")]));
+
+        assert_eq!(BlockStateString::try_from("stone[]").unwrap().properties(), &[]);
+    }
+
+    #[test]
+    fn test_blockstate_parse_error() {
+        let error = |input: &str| {
+            BlockStateString::try_from(input)
+                .map(|_| ())
+                .map_err(|error| (error.position, error.kind))
+        };
+
+        assert_eq!(error("Stone"), Err((0, BlockStateParseErrorKind::InvalidIdentifier)));
+        assert_eq!(error("oak_Stairs"), Err((4, BlockStateParseErrorKind::InvalidIdentifier)));
+        assert_eq!(error("stairs[facing]"), Err((13, BlockStateParseErrorKind::ExpectedEquals)));
+        assert_eq!(error("stairs[facing=]"), Err((14, BlockStateParseErrorKind::ExpectedValue)));
+        assert_eq!(error("stairs[facing=north"), Err((19, BlockStateParseErrorKind::ExpectedSeparator)));
+        assert_eq!(error("stairs[facing=north,]"), Err((20, BlockStateParseErrorKind::ExpectedName)));
+        assert_eq!(error("stairs[half=top,half=top]"), Err((16, BlockStateParseErrorKind::DuplicateProperty)));
+        assert_eq!(error("stairs[half=top]x"), Err((16, BlockStateParseErrorKind::TrailingCharacters)));
+    }
+
+    #[test]
+    fn test_blockstate_serde() {
+        let state = BlockStateString::new(Identifier::from_location("oak_stairs"), vec![("facing", "north"), ("half", "top")]);
+        assert_ser_tokens(&state, &[Token::Str("minecraft:oak_stairs[facing=north,half=top]")]);
+        assert_de_tokens(&state, &[Token::BorrowedStr("minecraft:oak_stairs[facing=north,half=top]")]);
+
+        assert_de_tokens_error::<BlockStateString>(&[Token::BorrowedStr("stairs[half")], "expected '=' at position 11");
+    }
+}
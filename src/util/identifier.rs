@@ -6,6 +6,7 @@ use nom::character::complete::char;
 use nom::combinator::map;
 use nom::error::Error;
 use nom::sequence::separated_pair;
+use nom::IResult;
 use serde::{Deserialize, Serialize, Serializer};
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -38,23 +39,31 @@ impl<'a> Identifier<'a> {
     pub fn location(&self) -> &'a str { self.location }
 }
 
+/// Parses an identifier from the start of `input`, returning the remaining
+/// input as well.
+///
+/// If no namespace is specified, the `minecraft` namespace is used.
+pub fn parse_identifier<'a>(input: &'a str) -> IResult<&'a str, Identifier<'a>> {
+    let namespace_domain = take_while::<_, _, Error<&'a str>>(|i| "0123456789abcdefghijklmnopqrstuvwxyz-_.".contains(i));
+    let location_domain = take_while::<_, _, Error<&'a str>>(|i| "0123456789abcdefghijklmnopqrstuvwxyz-_./".contains(i));
+    let namespace_location = separated_pair(namespace_domain, char(':'), location_domain);
+    let location_only =
+        map(take_while::<_, _, Error<&'a str>>(|i| "0123456789abcdefghijklmnopqrstuvwxyz-_./".contains(i)), |location: &'a str| ("minecraft", location));
+    map(alt((namespace_location, location_only)), |(namespace, location)| Identifier {
+        namespace,
+        location,
+    })(input)
+}
+
 impl<'a> TryFrom<&'a str> for Identifier<'a> {
     type Error = usize;
 
     fn try_from(input: &'a str) -> Result<Self, Self::Error> {
-        let namespace_domain = take_while::<_, _, Error<&'a str>>(|i| "0123456789abcdefghijklmnopqrstuvwxyz-_.".contains(i));
-        let location_domain = take_while::<_, _, Error<&'a str>>(|i| "0123456789abcdefghijklmnopqrstuvwxyz-_./".contains(i));
-        let namespace_location = separated_pair(namespace_domain, char(':'), location_domain);
-        let location_only =
-            map(take_while::<_, _, Error<&'a str>>(|i| "0123456789abcdefghijklmnopqrstuvwxyz-_./".contains(i)), |location: &'a str| ("minecraft", location));
-        let (input, (namespace, location)) = alt((namespace_location, location_only))(input).unwrap();
+        let (input, identifier) = parse_identifier(input).unwrap();
         if !input.is_empty() {
-            Err(location.len())
+            Err(identifier.location.len())
         } else {
-            Ok(Identifier {
-                namespace,
-                location,
-            })
+            Ok(identifier)
         }
     }
 }
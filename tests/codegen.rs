@@ -0,0 +1,115 @@
+//! Checks that generated code compiles and agrees with the data it was
+//! generated from.
+use data_compat_mc::blocks::mapping::{ArrayWidth, MappingArray};
+use data_compat_mc::codegen::blocks::BlockStateGenerator;
+use data_compat_mc::codegen::mapping::MappingArrayGenerator;
+use data_compat_mc::Identifier;
+
+#[rustfmt::skip]
+#[allow(dead_code)]
+#[path = "fixtures/blocks.rs"]
+mod blocks;
+
+#[rustfmt::skip]
+#[allow(dead_code)]
+#[path = "fixtures/mapping.rs"]
+mod mapping;
+
+#[rustfmt::skip]
+#[allow(dead_code)]
+#[path = "fixtures/mapping_u32.rs"]
+mod mapping_u32;
+
+const RAW: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/blocks.json"));
+const RULES: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rules.json"));
+const GENERATED_BLOCKS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/blocks.rs"));
+const GENERATED_MAPPING: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mapping.rs"));
+const GENERATED_MAPPING_U32: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mapping_u32.rs"));
+
+fn mapping_array() -> MappingArray {
+    MappingArray {
+        from: 1,
+        to: 2,
+        states: vec![Some(0), None, Some(3), Some(1), Some(3)],
+    }
+}
+
+/// A mapping with target ids that don't fit in `u16`.
+fn mapping_array_u32() -> MappingArray {
+    MappingArray {
+        from: 1,
+        to: 2,
+        states: vec![Some(70000), None, Some(0), Some(u16::MAX as u32)],
+    }
+}
+
+#[test]
+fn test_blocks_golden() {
+    let rules = data_compat_mc::load_rules(RULES).unwrap();
+    let data = data_compat_mc::compact(RAW, Some(&rules), None).unwrap();
+    let code = BlockStateGenerator::new(&data).generate().unwrap();
+    assert_eq!(code, GENERATED_BLOCKS, "tests/fixtures/blocks.rs is outdated, regenerate it using `mc-data codegen`");
+}
+
+#[test]
+fn test_blocks_round_trip() {
+    let rules = data_compat_mc::load_rules(RULES).unwrap();
+    let data = data_compat_mc::compact(RAW, Some(&rules), None).unwrap();
+    let index = data.index().unwrap();
+
+    let mut id = 0;
+    while let Some(expected) = index.state(id) {
+        let state = blocks::BlockState::from_state_id(id as u32).unwrap();
+        assert_eq!(state.to_state_id(), Some(id as u32));
+        assert_eq!(state.identifier(), expected.identifier.to_string());
+        id += 1;
+    }
+    assert_eq!(blocks::BlockState::from_state_id(id as u32), None);
+
+    let stairs = index.default_state(&Identifier::from_location("oak_stairs")).unwrap();
+    assert_eq!(blocks::BlockState::OakStairs(blocks::OakStairs::default()).to_state_id(), Some(stairs.id as u32));
+    let wire = blocks::RedstoneWire {
+        east: blocks::properties::RedstoneKind::Side,
+        north: blocks::properties::RedstoneKind::None,
+        power: 15,
+    };
+    let expected = index
+        .state_id(&Identifier::from_location("redstone_wire"), [("east", "side"), ("north", "none"), ("power", "15")])
+        .unwrap();
+    assert_eq!(blocks::BlockState::RedstoneWire(wire).to_state_id(), Some(expected as u32));
+    assert_eq!(blocks::BlockState::RedstoneWire(blocks::RedstoneWire { power: 200, ..wire }).to_state_id(), None);
+}
+
+#[test]
+fn test_mapping_golden() {
+    let array = mapping_array();
+    let code = MappingArrayGenerator::new(&array, ArrayWidth::U16, "mapping.bin")
+        .generate()
+        .unwrap();
+    assert_eq!(code, GENERATED_MAPPING, "tests/fixtures/mapping.rs is outdated, regenerate it from `mapping_array()`");
+    assert_eq!(mapping::MAPPING, array.to_bytes(ArrayWidth::U16).unwrap(), "tests/fixtures/mapping.bin is outdated");
+}
+
+#[test]
+fn test_mapping_map_state() {
+    let array = mapping_array();
+    assert_eq!((mapping::FROM_VERSION, mapping::TO_VERSION), (array.from, array.to));
+    assert_eq!(mapping::STATE_COUNT as usize, array.states.len());
+    for id in 0..=array.states.len() {
+        assert_eq!(mapping::map_state(id as u32), array.get(id), "blockstate {}", id);
+    }
+}
+
+#[test]
+fn test_mapping_u32_golden() {
+    let array = mapping_array_u32();
+    assert_eq!(array.width(), ArrayWidth::U32);
+    let code = MappingArrayGenerator::new(&array, ArrayWidth::U32, "mapping_u32.bin")
+        .generate()
+        .unwrap();
+    assert_eq!(code, GENERATED_MAPPING_U32, "tests/fixtures/mapping_u32.rs is outdated, regenerate it from `mapping_array_u32()`");
+    assert_eq!(mapping_u32::MAPPING, array.to_bytes(ArrayWidth::U32).unwrap(), "tests/fixtures/mapping_u32.bin is outdated");
+    for id in 0..=array.states.len() {
+        assert_eq!(mapping_u32::map_state(id as u32), array.get(id), "blockstate {}", id);
+    }
+}
@@ -0,0 +1,615 @@
+//! Blockstates of Minecraft
+//!
+//! This file was generated by mc-data, do not edit it manually.
+
+pub mod properties {
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum HorizontalFacing {
+        North,
+        South,
+        West,
+        East,
+    }
+
+    impl HorizontalFacing {
+        pub const COUNT: u32 = 4;
+
+        pub const fn index(self) -> u32 { self as u32 }
+
+        pub const fn from_index(index: u32) -> Option<Self> {
+            match index {
+                0 => Some(Self::North),
+                1 => Some(Self::South),
+                2 => Some(Self::West),
+                3 => Some(Self::East),
+                _ => None,
+            }
+        }
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::North => "north",
+                Self::South => "south",
+                Self::West => "west",
+                Self::East => "east",
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Half {
+        Top,
+        Bottom,
+    }
+
+    impl Half {
+        pub const COUNT: u32 = 2;
+
+        pub const fn index(self) -> u32 { self as u32 }
+
+        pub const fn from_index(index: u32) -> Option<Self> {
+            match index {
+                0 => Some(Self::Top),
+                1 => Some(Self::Bottom),
+                _ => None,
+            }
+        }
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Top => "top",
+                Self::Bottom => "bottom",
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Shape {
+        Straight,
+        InnerLeft,
+        InnerRight,
+        OuterLeft,
+        OuterRight,
+    }
+
+    impl Shape {
+        pub const COUNT: u32 = 5;
+
+        pub const fn index(self) -> u32 { self as u32 }
+
+        pub const fn from_index(index: u32) -> Option<Self> {
+            match index {
+                0 => Some(Self::Straight),
+                1 => Some(Self::InnerLeft),
+                2 => Some(Self::InnerRight),
+                3 => Some(Self::OuterLeft),
+                4 => Some(Self::OuterRight),
+                _ => None,
+            }
+        }
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Straight => "straight",
+                Self::InnerLeft => "inner_left",
+                Self::InnerRight => "inner_right",
+                Self::OuterLeft => "outer_left",
+                Self::OuterRight => "outer_right",
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Kind {
+        Top,
+        Bottom,
+        Double,
+    }
+
+    impl Kind {
+        pub const COUNT: u32 = 3;
+
+        pub const fn index(self) -> u32 { self as u32 }
+
+        pub const fn from_index(index: u32) -> Option<Self> {
+            match index {
+                0 => Some(Self::Top),
+                1 => Some(Self::Bottom),
+                2 => Some(Self::Double),
+                _ => None,
+            }
+        }
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Top => "top",
+                Self::Bottom => "bottom",
+                Self::Double => "double",
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Instrument {
+        Harp,
+        Basedrum,
+        Snare,
+        Hat,
+        Bass,
+    }
+
+    impl Instrument {
+        pub const COUNT: u32 = 5;
+
+        pub const fn index(self) -> u32 { self as u32 }
+
+        pub const fn from_index(index: u32) -> Option<Self> {
+            match index {
+                0 => Some(Self::Harp),
+                1 => Some(Self::Basedrum),
+                2 => Some(Self::Snare),
+                3 => Some(Self::Hat),
+                4 => Some(Self::Bass),
+                _ => None,
+            }
+        }
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Harp => "harp",
+                Self::Basedrum => "basedrum",
+                Self::Snare => "snare",
+                Self::Hat => "hat",
+                Self::Bass => "bass",
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum HopperFacing {
+        Down,
+        North,
+        South,
+        West,
+        East,
+    }
+
+    impl HopperFacing {
+        pub const COUNT: u32 = 5;
+
+        pub const fn index(self) -> u32 { self as u32 }
+
+        pub const fn from_index(index: u32) -> Option<Self> {
+            match index {
+                0 => Some(Self::Down),
+                1 => Some(Self::North),
+                2 => Some(Self::South),
+                3 => Some(Self::West),
+                4 => Some(Self::East),
+                _ => None,
+            }
+        }
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Down => "down",
+                Self::North => "north",
+                Self::South => "south",
+                Self::West => "west",
+                Self::East => "east",
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum RedstoneKind {
+        Up,
+        Side,
+        None,
+    }
+
+    impl RedstoneKind {
+        pub const COUNT: u32 = 3;
+
+        pub const fn index(self) -> u32 { self as u32 }
+
+        pub const fn from_index(index: u32) -> Option<Self> {
+            match index {
+                0 => Some(Self::Up),
+                1 => Some(Self::Side),
+                2 => Some(Self::None),
+                _ => None,
+            }
+        }
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Up => "up",
+                Self::Side => "side",
+                Self::None => "none",
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct OakStairs {
+    pub facing: properties::HorizontalFacing,
+    pub half: properties::Half,
+    pub shape: properties::Shape,
+    pub waterlogged: bool,
+}
+
+impl OakStairs {
+    pub const BASE_ID: u32 = 2;
+    pub const STATE_COUNT: u32 = 80;
+
+    /// The offset from [`Self::BASE_ID`], `None` if an integer property is
+    /// out of range.
+    pub const fn offset(self) -> Option<u32> {
+        let mut offset = 0;
+        offset = offset * 4 + self.facing.index();
+        offset = offset * 2 + self.half.index();
+        offset = offset * 5 + self.shape.index();
+        offset = offset * 2 + if self.waterlogged { 0 } else { 1 };
+        Some(offset)
+    }
+
+    pub const fn from_offset(offset: u32) -> Option<Self> {
+        if offset >= Self::STATE_COUNT {
+            return None;
+        }
+        let mut offset = offset;
+        let waterlogged = offset.is_multiple_of(2);
+        offset /= 2;
+        let shape = match properties::Shape::from_index(offset % 5) {
+            Some(value) => value,
+            None => return None,
+        };
+        offset /= 5;
+        let half = match properties::Half::from_index(offset % 2) {
+            Some(value) => value,
+            None => return None,
+        };
+        offset /= 2;
+        let facing = match properties::HorizontalFacing::from_index(offset % 4) {
+            Some(value) => value,
+            None => return None,
+        };
+        Some(Self {
+            facing,
+            half,
+            shape,
+            waterlogged,
+        })
+    }
+}
+
+impl Default for OakStairs {
+    fn default() -> Self {
+        match Self::from_offset(11) {
+            Some(state) => state,
+            None => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct OakSlab {
+    pub kind: properties::Kind,
+    pub waterlogged: bool,
+}
+
+impl OakSlab {
+    pub const BASE_ID: u32 = 82;
+    pub const STATE_COUNT: u32 = 6;
+
+    /// The offset from [`Self::BASE_ID`], `None` if an integer property is
+    /// out of range.
+    pub const fn offset(self) -> Option<u32> {
+        let mut offset = 0;
+        offset = offset * 3 + self.kind.index();
+        offset = offset * 2 + if self.waterlogged { 0 } else { 1 };
+        Some(offset)
+    }
+
+    pub const fn from_offset(offset: u32) -> Option<Self> {
+        if offset >= Self::STATE_COUNT {
+            return None;
+        }
+        let mut offset = offset;
+        let waterlogged = offset.is_multiple_of(2);
+        offset /= 2;
+        let kind = match properties::Kind::from_index(offset % 3) {
+            Some(value) => value,
+            None => return None,
+        };
+        Some(Self {
+            kind,
+            waterlogged,
+        })
+    }
+}
+
+impl Default for OakSlab {
+    fn default() -> Self {
+        match Self::from_offset(3) {
+            Some(state) => state,
+            None => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Wheat {
+    pub age: u8,
+}
+
+impl Wheat {
+    pub const BASE_ID: u32 = 88;
+    pub const STATE_COUNT: u32 = 8;
+
+    /// The offset from [`Self::BASE_ID`], `None` if an integer property is
+    /// out of range.
+    pub const fn offset(self) -> Option<u32> {
+        let mut offset = 0;
+        if self.age > 7 {
+            return None;
+        }
+        offset = offset * 8 + self.age as u32;
+        Some(offset)
+    }
+
+    pub const fn from_offset(offset: u32) -> Option<Self> {
+        if offset >= Self::STATE_COUNT {
+            return None;
+        }
+        let age = (offset % 8) as u8;
+        Some(Self {
+            age,
+        })
+    }
+}
+
+impl Default for Wheat {
+    fn default() -> Self {
+        match Self::from_offset(0) {
+            Some(state) => state,
+            None => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NoteBlock {
+    pub instrument: properties::Instrument,
+    pub note: u8,
+    pub powered: bool,
+}
+
+impl NoteBlock {
+    pub const BASE_ID: u32 = 96;
+    pub const STATE_COUNT: u32 = 250;
+
+    /// The offset from [`Self::BASE_ID`], `None` if an integer property is
+    /// out of range.
+    pub const fn offset(self) -> Option<u32> {
+        let mut offset = 0;
+        offset = offset * 5 + self.instrument.index();
+        if self.note > 24 {
+            return None;
+        }
+        offset = offset * 25 + self.note as u32;
+        offset = offset * 2 + if self.powered { 0 } else { 1 };
+        Some(offset)
+    }
+
+    pub const fn from_offset(offset: u32) -> Option<Self> {
+        if offset >= Self::STATE_COUNT {
+            return None;
+        }
+        let mut offset = offset;
+        let powered = offset.is_multiple_of(2);
+        offset /= 2;
+        let note = (offset % 25) as u8;
+        offset /= 25;
+        let instrument = match properties::Instrument::from_index(offset % 5) {
+            Some(value) => value,
+            None => return None,
+        };
+        Some(Self {
+            instrument,
+            note,
+            powered,
+        })
+    }
+}
+
+impl Default for NoteBlock {
+    fn default() -> Self {
+        match Self::from_offset(1) {
+            Some(state) => state,
+            None => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Hopper {
+    pub enabled: bool,
+    pub facing: properties::HopperFacing,
+}
+
+impl Hopper {
+    pub const BASE_ID: u32 = 346;
+    pub const STATE_COUNT: u32 = 10;
+
+    /// The offset from [`Self::BASE_ID`], `None` if an integer property is
+    /// out of range.
+    pub const fn offset(self) -> Option<u32> {
+        let mut offset = 0;
+        offset = offset * 2 + if self.enabled { 0 } else { 1 };
+        offset = offset * 5 + self.facing.index();
+        Some(offset)
+    }
+
+    pub const fn from_offset(offset: u32) -> Option<Self> {
+        if offset >= Self::STATE_COUNT {
+            return None;
+        }
+        let mut offset = offset;
+        let facing = match properties::HopperFacing::from_index(offset % 5) {
+            Some(value) => value,
+            None => return None,
+        };
+        offset /= 5;
+        let enabled = offset.is_multiple_of(2);
+        Some(Self {
+            enabled,
+            facing,
+        })
+    }
+}
+
+impl Default for Hopper {
+    fn default() -> Self {
+        match Self::from_offset(0) {
+            Some(state) => state,
+            None => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RedstoneWire {
+    pub east: properties::RedstoneKind,
+    pub north: properties::RedstoneKind,
+    pub power: u8,
+}
+
+impl RedstoneWire {
+    pub const BASE_ID: u32 = 356;
+    pub const STATE_COUNT: u32 = 144;
+
+    /// The offset from [`Self::BASE_ID`], `None` if an integer property is
+    /// out of range.
+    pub const fn offset(self) -> Option<u32> {
+        let mut offset = 0;
+        offset = offset * 3 + self.east.index();
+        offset = offset * 3 + self.north.index();
+        if self.power > 15 {
+            return None;
+        }
+        offset = offset * 16 + self.power as u32;
+        Some(offset)
+    }
+
+    pub const fn from_offset(offset: u32) -> Option<Self> {
+        if offset >= Self::STATE_COUNT {
+            return None;
+        }
+        let mut offset = offset;
+        let power = (offset % 16) as u8;
+        offset /= 16;
+        let north = match properties::RedstoneKind::from_index(offset % 3) {
+            Some(value) => value,
+            None => return None,
+        };
+        offset /= 3;
+        let east = match properties::RedstoneKind::from_index(offset % 3) {
+            Some(value) => value,
+            None => return None,
+        };
+        Some(Self {
+            east,
+            north,
+            power,
+        })
+    }
+}
+
+impl Default for RedstoneWire {
+    fn default() -> Self {
+        match Self::from_offset(128) {
+            Some(state) => state,
+            None => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BlockState {
+    Air,
+    Stone,
+    OakStairs(OakStairs),
+    OakSlab(OakSlab),
+    Wheat(Wheat),
+    NoteBlock(NoteBlock),
+    Hopper(Hopper),
+    RedstoneWire(RedstoneWire),
+}
+
+impl BlockState {
+    /// The blockstate id, `None` if an integer property is out of range.
+    pub const fn to_state_id(self) -> Option<u32> {
+        let (base_id, offset) = match self {
+            BlockState::Air => return Some(0),
+            BlockState::Stone => return Some(1),
+            BlockState::OakStairs(state) => (OakStairs::BASE_ID, state.offset()),
+            BlockState::OakSlab(state) => (OakSlab::BASE_ID, state.offset()),
+            BlockState::Wheat(state) => (Wheat::BASE_ID, state.offset()),
+            BlockState::NoteBlock(state) => (NoteBlock::BASE_ID, state.offset()),
+            BlockState::Hopper(state) => (Hopper::BASE_ID, state.offset()),
+            BlockState::RedstoneWire(state) => (RedstoneWire::BASE_ID, state.offset()),
+        };
+        match offset {
+            Some(offset) => Some(base_id + offset),
+            None => None,
+        }
+    }
+
+    pub const fn from_state_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(BlockState::Air),
+            1 => Some(BlockState::Stone),
+            2..=81 => match OakStairs::from_offset(id - OakStairs::BASE_ID) {
+                Some(state) => Some(BlockState::OakStairs(state)),
+                None => None,
+            },
+            82..=87 => match OakSlab::from_offset(id - OakSlab::BASE_ID) {
+                Some(state) => Some(BlockState::OakSlab(state)),
+                None => None,
+            },
+            88..=95 => match Wheat::from_offset(id - Wheat::BASE_ID) {
+                Some(state) => Some(BlockState::Wheat(state)),
+                None => None,
+            },
+            96..=345 => match NoteBlock::from_offset(id - NoteBlock::BASE_ID) {
+                Some(state) => Some(BlockState::NoteBlock(state)),
+                None => None,
+            },
+            346..=355 => match Hopper::from_offset(id - Hopper::BASE_ID) {
+                Some(state) => Some(BlockState::Hopper(state)),
+                None => None,
+            },
+            356..=499 => match RedstoneWire::from_offset(id - RedstoneWire::BASE_ID) {
+                Some(state) => Some(BlockState::RedstoneWire(state)),
+                None => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub const fn identifier(&self) -> &'static str {
+        match self {
+            BlockState::Air => "minecraft:air",
+            BlockState::Stone => "minecraft:stone",
+            BlockState::OakStairs(_) => "minecraft:oak_stairs",
+            BlockState::OakSlab(_) => "minecraft:oak_slab",
+            BlockState::Wheat(_) => "minecraft:wheat",
+            BlockState::NoteBlock(_) => "minecraft:note_block",
+            BlockState::Hopper(_) => "minecraft:hopper",
+            BlockState::RedstoneWire(_) => "minecraft:redstone_wire",
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! Blockstate mapping from version 1 to version 2
+//!
+//! This file was generated by mc-data, do not edit it manually.
+
+pub const FROM_VERSION: i32 = 1;
+pub const TO_VERSION: i32 = 2;
+pub const STATE_COUNT: u32 = 5;
+
+const HEADER_SIZE: usize = 28;
+
+/// The binary mapping, little-endian `u16` entries after a header
+pub static MAPPING: &[u8] = include_bytes!("mapping.bin");
+
+/// Translates a blockstate id, returns `None` if it has no counterpart.
+pub fn map_state(id: u32) -> Option<u32> {
+    let start = HEADER_SIZE + id as usize * 2;
+    let bytes = MAPPING.get(start..start + 2)?;
+    let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+    if value == u16::MAX {
+        None
+    } else {
+        Some(value as u32)
+    }
+}